@@ -1,47 +1,231 @@
+use std::collections::HashSet;
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
+    Order,
+    Storage,
     Env,
+    Deps,
     DepsMut,
+    Event,
     MessageInfo,
     Response,
-    BankMsg,
-    CosmosMsg,
+    StdResult,
+    StdError,
     Uint128,
+    Timestamp,
+    Decimal,
+    Coin,
+    Binary,
+    Addr,
 };
 
-use cw721_base::{ MintMsg };
+use cw721_base::{ MintMsg, Cw721Execute };
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
+use cw2::{ get_contract_version, set_contract_version };
+use semver::Version;
 use cw721_base::state::{ TokenInfo };
 
 use crate::state::{
     CW721Contract,
     Extension,
     CONFIG,
+    CONFIG_V1,
+    CONFIG_V5,
+    ConfigV5,
+    SUPPLY_V5,
+    Config,
+    BURNED,
+    BURNT_LIST,
+    ROLES,
+    PENDING_MINTER,
+    MINTER_RENOUNCED,
+    HANDOVER,
+    PendingMinter,
+    PAUSED,
+    PauseInfo,
+    PauseTarget,
+    AVAILABLE_IDX,
+    AVAILABLE_COUNT,
+    RESERVE_USED,
+    SUPPLY,
+    SupplyLedger,
+    TRAIT_COUNTS,
+    TRAIT_TOKENS,
+    PROVENANCE,
+    TOKEN_CONF,
+    TRAIT_BACKFILL,
+    TraitBackfill,
+    CONF_SEQ,
+    METADATA_FREEZE,
+    MetadataFreeze,
+    FrozenRange,
+    TRAIT_HISTORY,
+    TraitChange,
+    TraitUpdate,
+    UPGRADE_CONSENT,
+    EVOLVE_SEQ,
+    LINEAGE,
+    EVOLVED_INTO,
+    Lineage,
+    EDITIONS,
+    Edition,
+    SERIES,
+    Series,
+    TOKEN_SERIES,
     Metadata,
+    Partner,
+    Role,
     Trait,
 };
 
 use crate::helpers::{
+    _can_burn_batch,
+    _can_burn_batch_size,
     _can_mint,
     _can_pay,
     _can_store,
     _can_update,
+    _can_role,
     _try_mint,
     _try_store,
     __update_total,
     __burn_token,
+    __retire_token,
+    _is_mint_live,
+    _not_paused,
+    _not_handing_over,
+    _can_transfer,
+    __handover_stored,
+    _can_mint_public,
+    _can_mint_wallet,
+    _can_use_partner,
+    _can_pay_at,
+    _payment_msgs,
+    _mint_price,
+    __use_partner,
+    _can_use_voucher,
+    _can_lazy_mint,
+    _validate_pubkey,
+    _peek_available,
+    _peek_series_available,
+    __use_voucher,
+    __update_minted,
+    _next_available,
+    __push_available,
+    _assert_supply,
+    _mint_phase,
+    _mint_event,
+    _burn_event,
+    _store_event,
+    PHASE_AIRDROP,
+    PHASE_LAZY,
+    PHASE_EVOLVE,
+    PHASE_EDITION,
+    _edition_token,
+    _validate_edition,
+    _can_reserve_edition,
+    _not_edition_id,
+    EVOLVED_TOKEN_PREFIX,
+    __update_supply,
+    __push_series,
+    _next_series_available,
+    _series_available_count,
+    _series_config,
+    DEFAULT_SERIES_TOKEN_ID_TEMPLATE,
+    _render_template,
+    __record_provenance,
+    _token_store_conf,
+    _token_schema_conf,
+    __save_store_conf,
+    _validate_metadata,
+    _next_conf_id,
+    _validate_store_conf,
+    _store_columns,
+    _build_traits,
+    __index_traits,
+    __unindex_traits,
+    _can_edit_metadata,
+    _can_edit_collection,
+    _can_upgrade_traits,
+    _can_upgrade_with_consent,
+    _next_upgrade_seq,
+    __apply_traits,
+    _evolve_traits,
+    _token_traits,
+    _live_tokens,
+    _minted_count,
+    DEFAULT_TOKEN_ID_TEMPLATE,
+    DEFAULT_NAME_TEMPLATE,
+    DEFAULT_IMAGE_TEMPLATE,
+    _reserve_remaining,
     __update_burnt_amount,
     __update_burnt_list
 };
 
 use crate::error::ContractError;
 
-use crate::msg::{ BatchStoreMsg, BatchMintMsg, StoreConfMsg };
+use crate::msg::{
+    BatchStoreMsg,
+    BatchMintMsg,
+    StoreConfMsg,
+    UpdateConfigMsg,
+    ConfigField,
+    MigrateMsg,
+    RoleHolders,
+    RolesResponse,
+    PendingMinterResponse,
+    PauseResponse,
+    PauseState,
+    AirdropMsg,
+    ReserveResponse,
+    SingleMintMsg,
+    LazyMintMsg,
+    SimulateMintResponse,
+    SupplyInfoResponse,
+    TraitFrequencyResponse,
+    TraitDistributionResponse,
+    RarityResponse,
+    PartnerToken,
+    SignedVoucher,
+    FreezeResponse,
+    ProvenanceResponse,
+    OwnerTokensByTraitResponse,
+    UpgradePayload,
+    TraitHistoryResponse,
+    LineageResponse,
+    StoreEditionMsg,
+    EditionMintMsg,
+    EditionResponse,
+    UpdateSeriesMsg,
+    SeriesResponse,
+    AllSeriesResponse,
+};
+
+use cw721::TokensResponse;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw721-offchain-randomization";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_SWEEP_LIMIT: u32 = 30;
+const MAX_SWEEP_LIMIT: u32 = 100;
+
+const DEFAULT_QUERY_LIMIT: u32 = 10;
+const MAX_QUERY_LIMIT: u32 = 100;
+const MAX_SCAN_LIMIT: u32 = 300;
 
 ANDRE: THIS FUNCTION EXECUTES THE BURN OF A cw721 TOKEN
 pub fn execute_burn(
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
     token_id: String,   ANDRE: THIS PARAMETER IDENTIFIES THE THE TOKEN TO BE BURNED
 ) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Burn)?;
+
     let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE INTERFACE TO HANDLE OPERATIONS??
     let config = CONFIG.load(deps.storage)?; ANDRE: LOADS THE STORED CONFIGURATION
 
@@ -56,35 +240,39 @@ pub fn execute_burn(
 
         ANDRE: THE NEXT 3 LINES OF CODE WILL PERFORM CHANGES TO THE CONST VARIABLES CREATED IN THE state.rs FILE. 
         THIS FUNCTIONS LIVE ON THE helpers.rs file, WHICH WILL BE ANALYSED IN THE FUTURE
-        __burn_token(&cw721_contract, deps.storage, token_id.clone())?; 
+        let owner = __burn_token(&cw721_contract, deps.storage, token_id.clone())?; 
 
         __update_burnt_amount(deps.storage, &info.sender)?;
 
         __update_burnt_list(deps.storage, &info.sender, &token_id)?;
 
+        _assert_supply(deps.storage)?;
+
         return Ok(Response::new()
             .add_attribute("action", "burn")
             .add_attribute("type", "owner_burn")
-            .add_attribute("token_id", token_id))
+            .add_attribute("token_id", token_id.clone())
+            .add_event(_burn_event(&token_id, &owner)))
     }
 
     ANDRE: THIS IF() HANDLES THE BURN DONE BY THE MINTER OF THE CONTRACT
     if config.minter_can_burn == true {
         // validate sender permissions
-        ANDRE: THE NEXT 3 LINES OF CODE WILL PERFORM CHANGES TO THE CONST VARIABLES CREATED IN THE state.rs FILE. 
-        THIS FUNCTIONS LIVE ON THE helpers.rs file, WHICH WILL BE ANALYSED IN THE FUTURE
-        _can_update(&deps, &info)?; ANDRE: CHECKS IF THE EXECUTE ADDRESS IN THE MINTER
+        _can_role(&deps, &info, Role::Burner)?;
 
-        __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
+        let owner = __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
 
         ANDRE: THE CALL TO UPDATE THE BURNT AMOUNT (__update_burnt_amount) IS NOT DONE, PROBABLY BECAUSE
         THERE IS NO NEED TO KEEP TRACK OF TOKENS BURNT BY THE MINTER???
         __update_burnt_list(deps.storage, &info.sender, &token_id)?;
 
+        _assert_supply(deps.storage)?;
+
         return Ok(Response::new()
             .add_attribute("action", "burn")
             .add_attribute("type", "minter_burn")
-            .add_attribute("token_id", token_id))
+            .add_attribute("token_id", token_id.clone())
+            .add_event(_burn_event(&token_id, &owner)))
     }
 
     Ok(Response::new()
@@ -96,75 +284,68 @@ pub fn execute_burn(
 }
 
 pub fn execute_burn_batch(
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
     tokens: Vec<String>
 ) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Burn)?;
+
     let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
     let config = CONFIG.load(deps.storage)?;
 
-    ANDRE: THE NEXT 2 IF CLAUSES, CHECKS IF THE AMOUNT OF TOKENS TO BE BURNT ARE BETWEEN 1 AND 30
-    MAYBE THEY CAN BE ENCAPSULATED IN A FUNCTION IN THE helpers.rs FILE
-    MAYBE INCLUDE THIS RANGE (1..31) AS A PARAMETER IN THE CONFIG FILE, SO THAT IT CAN BE UPDATED OVER TIME?
-    if tokens.len() > 30 {
-        return Err(ContractError::RequestTooLarge{ size: tokens.len() })
-    }
-
-    if tokens.len() == 0 {
-        return Err(ContractError::RequestTooSmall{ size: tokens.len() })
-    }
+    _can_burn_batch_size(&config, tokens.len())?;
 
     ANDRE: THIS IF CLAUSE HANDLES THE BURN LIST OF THE TOKENS DONE BY THE OWNER OF THE TOKENS
     if config.owners_can_burn == true {
-        let mut burnt_tokens = vec![];
-
-        for token_id in tokens {
-            let token = cw721_contract.tokens.load(deps.storage, &token_id)?;
-
-            ANDRE: IF THE LAST TOKEN IN THE LIST DONT PASS THIS IF, ALL OF THE PREVIOUS ACTIONS WILL BE REVERTED (NOT SURE)
-            IF THAT IS THE CASE, AND IF WE WANT TO KEEP THE LOGIC OF BURN ALL OR NONE, WE SHOULD EXTRACT THIS VERIFICATION TO BE DONE BEFORE THIS FOR LOOP
-            IT WILL PROBABLY OPTIMIZE THE CODE FOR A CASE WHERE THE SENDER IS TRYING TO BURN A TOKEN THAT DOES NOT BELONG TO HIM
-            if token.owner != info.sender {
-                return Err(ContractError::Unauthorized {})
-            }
+        // validate every token before touching the state, burn all or none
+        _can_burn_batch(deps.storage, &cw721_contract, &tokens, Some(&info.sender))?;
 
-            __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
+        let mut events: Vec<Event> = vec![];
+        for token_id in &tokens {
+            let owner = __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
 
             __update_burnt_amount(deps.storage, &info.sender)?;
 
-            __update_burnt_list(deps.storage, &info.sender, &token_id)?;
+            __update_burnt_list(deps.storage, &info.sender, token_id)?;
 
-            burnt_tokens.push(token_id);
+            events.push(_burn_event(token_id, &owner));
         }
 
+        _assert_supply(deps.storage)?;
+
         return Ok(Response::new()
             .add_attribute("action", "burn_batch")
             .add_attribute("type", "owner_burn")
-            .add_attribute("tokens", String::from(format!("[{}]", burnt_tokens.join(","))))
+            .add_attribute("tokens", String::from(format!("[{}]", tokens.join(","))))
+            .add_events(events)
         )
     }
 
     ANDRE: THIS IF CLAUSE HANDLES THE BURN LIST OF TOKENS DONE BY THE mINTER OF THE TOKENS
     if config.minter_can_burn == true {
         // validate sender permissions
-        _can_update(&deps, &info)?;
+        _can_role(&deps, &info, Role::Burner)?;
 
-        let mut burnt_tokens = vec![]; ANDRE: DECLARE ONLY ONCE BEFORE THE IF CLAUSES, BECAUSE IT WILL ONLY BE USED IN ONE OF THE CLAUSES
-                                            WHY DO WE NEED THIS VECTOR? CANT WE USE THE VECTOR THAT IS PASSES IN THE FUNCTION SIGNATURE?
-                                            THIS IS ONLY USED TO COMPOSE THE RESPONSE...
+        // validate every token before touching the state, burn all or none
+        _can_burn_batch(deps.storage, &cw721_contract, &tokens, None)?;
 
-        for token_id in tokens {
-            __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
+        let mut events: Vec<Event> = vec![];
+        for token_id in &tokens {
+            let owner = __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
 
-            __update_burnt_list(deps.storage, &info.sender, &token_id)?;
+            __update_burnt_list(deps.storage, &info.sender, token_id)?;
 
-            burnt_tokens.push(token_id);
+            events.push(_burn_event(token_id, &owner));
         }
 
+        _assert_supply(deps.storage)?;
+
         return Ok(Response::new()
             .add_attribute("action", "burn")
             .add_attribute("type", "minter_burn")
-            .add_attribute("tokens", String::from(format!("[{}]", burnt_tokens.join(","))))
+            .add_attribute("tokens", String::from(format!("[{}]", tokens.join(","))))
+            .add_events(events)
         )
     }
 
@@ -176,261 +357,2630 @@ pub fn execute_burn_batch(
     )
 }
 
-pub fn execute_mint(
+// updates the contract config, every field is optional and only the given ones are changed.
+// the config-admin role can change everything except the funds wallet, that belongs to the treasury role
+pub fn execute_update_config(
     env: Env,
     deps: DepsMut,
     info: MessageInfo,
+    msg: UpdateConfigMsg,
 ) -> Result<Response, ContractError> {
-    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
-    let config = CONFIG.load(deps.storage)?;
-    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER ADDRESS
-    let current_count = cw721_contract.token_count(deps.storage)?; ANDRE: LOADS THE COUNT OF ALREADY MINTED TOKENS
+    // validate sender permissions
+    _can_role(&deps, &info, Role::ConfigAdmin)?;
 
-    // check if we can mint
-    ANDRE: THIS FUNCTION VALIDATES THAT A MINT CAN HAPPEN
-    let current_token_id = _can_mint(
-        &current_count,
-        &env.block.time,
-        &config.start_mint,
-        config.token_total,
-        config.token_supply,
-        &minter,
-        &info.sender
-    )?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let live = _is_mint_live(&config, &env.block.time);
 
-    // validate funds according to set price
-    let coin_found = _can_pay(&config, &info, Uint128::from(1u32))?; VALIDATES THAT THE EXECUTER HAVE FUNDS TO PAY THE MINT
+    let mut res = Response::new().add_attribute("action", "update_config");
 
-    ANDRE: TRY TO EXECTUTE THE MINT
-    _try_mint(
-        deps.storage,
-        &info.sender,
-        &minter,
-        &cw721_contract,
-        &current_token_id.to_string()
-    )?;
+    // the optional fields that can be unset again. a field can't be cleared and set in the same update
+    for field in msg.clear.clone().unwrap_or_default() {
+        let (name, set) = match field {
+            ConfigField::EndMint => ("end_mint", msg.end_mint.is_some()),
+            ConfigField::MaxPerWallet => ("max_per_wallet", msg.max_per_wallet.is_some()),
+            ConfigField::VoucherSigner => ("voucher_signer", msg.voucher_signer.is_some()),
+            ConfigField::LazySigner => ("lazy_signer", msg.lazy_signer.is_some()),
+        };
+        if set {
+            return Err(ContractError::ConflictingUpdate { field: name.to_string() })
+        }
 
-    // send funds to the configured funds wallet
-    // send the info below
-    Ok(Response::new()
-        .add_attribute("action", "mint")
-        .add_attribute("owner", info.sender)
-        .add_attribute("token_id", current_count.to_string()) ANDRE: THE COUNT IS THE TOKEN_ID ???
-        .add_message(
-            ANDRE: THIS MESSAGE IS SENT TO THE BANK MODULE, SO THAT THE EXECUTER PAY FOR THE MINT
-            IS THE EXECUTER ONLY ALLOWED TO PAY IN THE NATIVE TOKEN? IF SO, HOW COULD WE ALLOW ALSO CW20 TOKENS
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: config.funds_wallet.to_string(),
-                amount: vec![coin_found],
-            }.clone())
-        )
-    )
-}
+        let before = match field {
+            ConfigField::EndMint => _fmt_time(&config.end_mint.take()),
+            ConfigField::MaxPerWallet => config.max_per_wallet.take().map(|m| m.to_string()).unwrap_or_else(|| "None".to_string()),
+            ConfigField::VoucherSigner => config.voucher_signer.take().map(|k| k.to_base64()).unwrap_or_else(|| "None".to_string()),
+            ConfigField::LazySigner => config.lazy_signer.take().map(|k| k.to_base64()).unwrap_or_else(|| "None".to_string()),
+        };
 
-pub fn execute_mint_batch(
-    env: Env,
-    deps: DepsMut,
-    info: MessageInfo,
-    msg: BatchMintMsg,
-) -> Result<Response, ContractError> {
-    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
+        res = res
+            .add_attribute(format!("{}_before", name), before)
+            .add_attribute(format!("{}_after", name), "None");
+    }
 
-    let config = CONFIG.load(deps.storage)?;
+    if let Some(owners_can_burn) = msg.owners_can_burn {
+        res = res
+            .add_attribute("owners_can_burn_before", config.owners_can_burn.to_string())
+            .add_attribute("owners_can_burn_after", owners_can_burn.to_string());
+        config.owners_can_burn = owners_can_burn;
+    }
 
-    let minted_total = cw721_contract.token_count(deps.storage)?; ANDRE: LOADS THE CURRENT MINT TOTAL
-    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER DEFINED IN THE CONTRACT
+    if let Some(minter_can_burn) = msg.minter_can_burn {
+        res = res
+            .add_attribute("minter_can_burn_before", config.minter_can_burn.to_string())
+            .add_attribute("minter_can_burn_after", minter_can_burn.to_string());
+        config.minter_can_burn = minter_can_burn;
+    }
 
-    let mut mint_amount = msg.amount; ANDRE: ASSIGNS THE MESSAGE PROPERTY AMMOUNT TO A MUT VARIABLE
+    res = _update_sale(res, &mut config, live, SaleUpdate {
+        gated_mint: msg.gated_mint,
+        start_mint: msg.start_mint,
+        end_mint: msg.end_mint,
+        cost_amount: msg.cost_amount,
+        cost_denom: msg.cost_denom,
+    })?;
+
+    if let Some(funds_wallet) = msg.funds_wallet {
+        _can_role(&deps, &info, Role::Treasury)?;
+
+        let funds_wallet = deps.api.addr_validate(&funds_wallet)?;
+        res = res
+            .add_attribute("funds_wallet_before", config.funds_wallet.to_string())
+            .add_attribute("funds_wallet_after", funds_wallet.to_string());
+        config.funds_wallet = funds_wallet;
+    }
+
+    if let Some(max_mint_batch) = msg.max_mint_batch {
+        if max_mint_batch.is_zero() {
+            return Err(ContractError::InvalidBatchLimits { min: Uint128::from(1u32), max: max_mint_batch })
+        }
 
-    ANDRE: IF BatchMintMsg ARRIVES WITH A 0 AS VALUE, WHY CHANGET IT TO 1?? AN ERROR SHOULD BE THROWN
-    if mint_amount == Uint128::from(0u32) {
-        mint_amount = Uint128::from(1u32)
+        res = res
+            .add_attribute("max_mint_batch_before", config.max_mint_batch.to_string())
+            .add_attribute("max_mint_batch_after", max_mint_batch.to_string());
+        config.max_mint_batch = max_mint_batch;
     }
 
-    ANDRE: IF BatchMintMsg ARRIVES WITH A VALUE GRATER THEN THE ALLOWED MINT SIZE, WHY CHANGET IT TO THE MAX?? AN ERROR SHOULD BE THROWN
-    if mint_amount > config.max_mint_batch {
-        mint_amount = config.max_mint_batch
+    if let Some(min_burn_batch) = msg.min_burn_batch {
+        res = res
+            .add_attribute("min_burn_batch_before", config.min_burn_batch.to_string())
+            .add_attribute("min_burn_batch_after", min_burn_batch.to_string());
+        config.min_burn_batch = min_burn_batch;
     }
 
-    // check if we can mint
-    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
-    let mut current_token_id = _can_mint(
-        &minted_total,
-        &env.block.time,
-        &config.start_mint,
-        config.token_total,
-        config.token_supply,
-        &minter,
-        &info.sender
-    )?;
+    if let Some(max_burn_batch) = msg.max_burn_batch {
+        res = res
+            .add_attribute("max_burn_batch_before", config.max_burn_batch.to_string())
+            .add_attribute("max_burn_batch_after", max_burn_batch.to_string());
+        config.max_burn_batch = max_burn_batch;
+    }
 
-    // validate funds according to set price and total to mint
-    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
-    let mut coin_found = _can_pay(&config, &info, mint_amount)?;
+    if config.min_burn_batch.is_zero() || config.min_burn_batch > config.max_burn_batch {
+        return Err(ContractError::InvalidBatchLimits { min: config.min_burn_batch, max: config.max_burn_batch })
+    }
 
-    ANDRE: VARIABLE CREATED TO KEEP TRACK OF TOTAL MINTED TOKENS
-    let mut total_minted = 0u32;
+    if let Some(partners) = msg.partners {
+        let mut checked: Vec<Partner> = vec![];
+        for partner in partners {
+            // a full discount would leave nothing to send to the funds wallet
+            if partner.discount >= Decimal::one() || partner.max_uses == 0 {
+                return Err(ContractError::InvalidPartner { contract: partner.address })
+            }
 
-    ANDRE: VECTOR THAT WILL CONTAIN THE IDS OF MINTED TOKENS, TO BE USED IN THE RESPONSE
-    let mut ids: Vec<String> = vec![];
+            checked.push(Partner {
+                address: deps.api.addr_validate(&partner.address)?,
+                discount: partner.discount,
+                max_uses: partner.max_uses,
+            });
+        }
 
-    ANDRE: WHILE LOOP THAT WILL MINT AND UPDATE THE TRACKING VARIABLES STATE
-    while Uint128::from(total_minted) < mint_amount {
-        ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
-        let res = _try_mint(
-            deps.storage,
-            &info.sender,
-            &minter,
-            &cw721_contract,
-            &current_token_id.to_string()
-        );
-
-        ANDRE: CHECKS IF THE PREVIOUS CALL IS SUCCEFULL AND THEN UPDATES THE TRACKING VARIABLES
-        if res.is_ok() {
-            total_minted += 1;
-            current_token_id += Uint128::from(1u32);
-            ids.push(current_token_id.to_string())
+        res = res
+            .add_attribute("partners_before", config.partners.len().to_string())
+            .add_attribute("partners_after", checked.len().to_string());
+        config.partners = checked;
+    }
+
+    if let Some(voucher_signer) = msg.voucher_signer {
+        _validate_pubkey(&voucher_signer)?;
+
+        res = res
+            .add_attribute("voucher_signer_before", config.voucher_signer.as_ref().map(|k| k.to_base64()).unwrap_or_else(|| "None".to_string()))
+            .add_attribute("voucher_signer_after", voucher_signer.to_base64());
+        config.voucher_signer = Some(voucher_signer);
+    }
+
+    if let Some(lazy_signer) = msg.lazy_signer {
+        _validate_pubkey(&lazy_signer)?;
+
+        res = res
+            .add_attribute("lazy_signer_before", config.lazy_signer.as_ref().map(|k| k.to_base64()).unwrap_or_else(|| "None".to_string()))
+            .add_attribute("lazy_signer_after", lazy_signer.to_base64());
+        config.lazy_signer = Some(lazy_signer);
+    }
+
+    if let Some(max_per_wallet) = msg.max_per_wallet {
+        res = res
+            .add_attribute("max_per_wallet_before", config.max_per_wallet.map(|m| m.to_string()).unwrap_or_else(|| "None".to_string()))
+            .add_attribute("max_per_wallet_after", max_per_wallet.to_string());
+        config.max_per_wallet = Some(max_per_wallet);
+    }
+
+    if let Some(reserved) = msg.reserved {
+        let used = RESERVE_USED.may_load(deps.storage)?.unwrap_or_default();
+        if reserved < used {
+            return Err(ContractError::ReservedSupply { reserved: used })
+        }
+
+        // the reserve is taken from the collection supply, it can't be larger
+        if reserved > config.token_supply {
+            return Err(ContractError::MaxTokenSupply {})
         }
+
+        res = res
+            .add_attribute("reserved_before", config.reserved.to_string())
+            .add_attribute("reserved_after", reserved.to_string());
+        config.reserved = reserved;
     }
 
-    ANDRE: WHY DO WE NEED TO UPDATE THIS VARIABLE??? tHE RESPONSE FORM THE _can_pay() SHOULD ALREADY CONTAIN THE CORRECT VALUE
-    coin_found.amount = config.cost_amount * Uint128::from(total_minted);
+    if let Some(store_conf) = msg.store_conf {
+        _validate_store_conf(&store_conf)?;
+
+        res = res
+            .add_attribute("store_conf_before", config.store_conf.as_ref().map(|c| c.name.clone()).unwrap_or_else(|| "None".to_string()))
+            .add_attribute("store_conf_after", store_conf.name.clone());
+        config.store_conf = Some(store_conf);
+    }
+
+    if let Some(evolve) = msg.evolve {
+        if evolve.inputs < 2 {
+            return Err(ContractError::InvalidEvolve { reason: "at least 2 inputs".to_string() })
+        }
+
+        res = res
+            .add_attribute("evolve_inputs_before", config.evolve.as_ref().map(|e| e.inputs.to_string()).unwrap_or_else(|| "None".to_string()))
+            .add_attribute("evolve_inputs_after", evolve.inputs.to_string());
+        config.evolve = Some(evolve);
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(res)
+}
+
+fn _fmt_time(time: &Option<Timestamp>) -> String {
+    time.map(|t| t.to_string()).unwrap_or_else(|| "None".to_string())
+}
+
+// the schedule and price fields of UpdateConfig and UpdateSeries
+struct SaleUpdate {
+    gated_mint: Option<Timestamp>,
+    start_mint: Option<Timestamp>,
+    end_mint: Option<Timestamp>,
+    cost_amount: Option<Uint128>,
+    cost_denom: Option<String>,
+}
+
+// applies a sale update to the collection config, or to the config a series mints with (see _series_config).
+// 'live' is whether the sale was live before the update, the price can't change while it is
+fn _update_sale(
+    mut res: Response,
+    config: &mut Config,
+    live: bool,
+    msg: SaleUpdate,
+) -> Result<Response, ContractError> {
+    if let Some(start_mint) = msg.start_mint {
+        res = res
+            .add_attribute("start_mint_before", _fmt_time(&config.start_mint))
+            .add_attribute("start_mint_after", start_mint.to_string());
+        config.start_mint = Some(start_mint);
+    }
+
+    if let Some(end_mint) = msg.end_mint {
+        res = res
+            .add_attribute("end_mint_before", _fmt_time(&config.end_mint))
+            .add_attribute("end_mint_after", end_mint.to_string());
+        config.end_mint = Some(end_mint);
+    }
+
+    if let (Some(start), Some(end)) = (config.start_mint, config.end_mint) {
+        if start >= end {
+            return Err(ContractError::InvalidMintWindow {})
+        }
+    }
+
+    if let Some(gated_mint) = msg.gated_mint {
+        res = res
+            .add_attribute("gated_mint_before", _fmt_time(&config.gated_mint))
+            .add_attribute("gated_mint_after", gated_mint.to_string());
+        config.gated_mint = Some(gated_mint);
+    }
+
+    if let (Some(gated), Some(start)) = (config.gated_mint, config.start_mint) {
+        if gated >= start {
+            return Err(ContractError::InvalidMintWindow {})
+        }
+    }
+
+    // the price can't change under the feet of the buyers
+    if msg.cost_amount.is_some() || msg.cost_denom.is_some() {
+        if live {
+            return Err(ContractError::MintIsLive {})
+        }
+    }
+
+    if let Some(cost_amount) = msg.cost_amount {
+        res = res
+            .add_attribute("cost_amount_before", config.cost_amount.to_string())
+            .add_attribute("cost_amount_after", cost_amount.to_string());
+        config.cost_amount = cost_amount;
+    }
+
+    if let Some(cost_denom) = msg.cost_denom {
+        if cost_denom.trim().is_empty() {
+            return Err(ContractError::InvalidDenom {})
+        }
+
+        res = res
+            .add_attribute("cost_denom_before", config.cost_denom.clone())
+            .add_attribute("cost_denom_after", cost_denom.clone());
+        config.cost_denom = cost_denom;
+    }
+
+    Ok(res)
+}
+
+pub fn execute_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    // only the admin can hand out roles
+    _can_update(&deps, &info)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (role.as_str(), &address), &true)?;
 
-    // send funds to the configured funds wallet
-    // send the info below
     Ok(Response::new()
-        .add_attribute("action", "mint_batch")
-        .add_attribute("owner", info.sender)
-        .add_attribute("requested", msg.amount.to_string())
-        .add_attribute("minted", total_minted.to_string())
-        .add_attribute("cost", coin_found.amount.to_string())
-        .add_attribute("list", String::from(format!("[{}]", ids.join(","))))
-        .add_message(
-            ANDRE: THIS MESSAGE IS SENT TO THE BANK MODULE, SO THAT THE EXECUTER PAY FOR THE BATCH MINT
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: config.funds_wallet.to_string(),
-                amount: vec![coin_found],
-            }.clone())
-        )
+        .add_attribute("action", "grant_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address)
     )
 }
 
-ANDRE: THIS FUNCTION STORES THE NFT ON CHAIN - I THINK THIS IS A PRE MINT STEP. NOT SURE...
-pub fn execute_store(
+pub fn execute_revoke_role(
     deps: DepsMut,
     info: MessageInfo,
-    nft_data: MintMsg<Extension>,
+    role: Role,
+    address: String,
 ) -> Result<Response, ContractError> {
-    // validate sender permissions
-    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
-    _can_store(&deps, &info)?;
-
-    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
-    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER DEFINED IN THE CONTRACT
+    // only the admin can take roles away
+    _can_update(&deps, &info)?;
 
-    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
-    _try_store(deps.storage, &nft_data, &minter, &cw721_contract)?;
+    let address = deps.api.addr_validate(&address)?;
+    if !ROLES.has(deps.storage, (role.as_str(), &address)) {
+        return Err(ContractError::RoleNotFound { role: role.to_string(), address: address.to_string() })
+    }
 
-    ANDRE: WHY NOT USE THE UPDATE FUNCTION ON THE CONFIG, INSTEAD OF LOADING AND UPDATING, AND THE SAVING
-    let total = CONFIG.load(deps.storage)?.token_total + Uint128::from(1u8);
-    __update_total(deps.storage, total)?;
+    ROLES.remove(deps.storage, (role.as_str(), &address));
 
     Ok(Response::new()
-        .add_attribute("action", "store")
-        .add_attribute("token_total", total.to_string())
+        .add_attribute("action", "revoke_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address)
     )
 }
 
-NDRE: THIS FUNCTION STORES A BATCH OF NFTs ON CHAIN - I THINK THIS IS A PRE MINT STEP. NOT SURE...
-pub fn execute_store_batch(
+// first step of the minter (admin) transfer, the new minter still has to accept it
+pub fn execute_propose_minter(
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
-    data: BatchStoreMsg,
+    new_minter: String,
+    expires: Option<Expiration>,
 ) -> Result<Response, ContractError> {
     // validate sender permissions
-    _can_store(&deps, &info)?;
+    _can_update(&deps, &info)?;
 
-    let cw721_contract = CW721Contract::default(); ANDRE: CREATES THE INTERFACE
-    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOAD THE MINTER INFORMATION
+    // the stored tokens of the previous transfer must be handed over first
+    _not_handing_over(deps.storage)?;
 
-    let mut total = CONFIG.load(deps.storage)?.token_total;
-    ANDRE: THIS FUNCTION IS VERY SIMILAR TO THE PREVIOUS ONE, EXCEPT THIS FOR LOOP
-    for nft_data in data.batch {
-        _try_store(deps.storage, &nft_data, &minter, &cw721_contract)?;
-        ANDRE: INSTEAD OF UPDATING THE TOTAL VARIABLE, WHY DON'T USE DIRECTLY THE data LENGHT??
-        total += Uint128::from(1u8)
+    let new_minter = deps.api.addr_validate(&new_minter)?;
+    if new_minter == info.sender {
+        return Err(ContractError::SameMinter {})
+    }
+
+    if let Some(expiration) = expires {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::Expired {})
+        }
     }
 
-    __update_total(deps.storage, total)?;
+    PENDING_MINTER.save(deps.storage, &PendingMinter {
+        address: new_minter.clone(),
+        expires,
+    })?;
+
+    let expires = expires.map(|e| e.to_string()).unwrap_or_else(|| "never".to_string());
 
     Ok(Response::new()
-        .add_attribute("action", "store_batch")
-        .add_attribute("token_total", total.to_string())
+        .add_attribute("action", "propose_minter")
+        .add_event(Event::new("minter_transfer_proposed")
+            .add_attribute("minter", info.sender)
+            .add_attribute("pending_minter", new_minter)
+            .add_attribute("expires", expires)
+        )
     )
 }
 
-pub fn execute_store_conf(
+// second step of the minter transfer, called by the proposed address.
+// stored tokens are owned by the minter until they are minted, so they are handed over as well. the first
+// page moves here, big collections finish with HandoverStored
+pub fn execute_accept_minter(
+    env: Env,
     deps: DepsMut,
     info: MessageInfo,
-    msg: StoreConfMsg,
-)-> Result<Response, ContractError> {
-    // validate sender permissions
-    _can_store(&deps, &info)?;
-
+) -> Result<Response, ContractError> {
     let cw721_contract = CW721Contract::default();
-    let minter = cw721_contract.minter.load(deps.storage)?;
 
-    let mut config = CONFIG.load(deps.storage)?.store_conf;
-    if config.is_none() && msg.conf.is_none() {
-        return Err(ContractError::NoConfiguration {})
+    if MINTER_RENOUNCED.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::Renounced {})
     }
 
-    if msg.conf.is_some() {
-        config = msg.conf
+    let pending = PENDING_MINTER.may_load(deps.storage)?.ok_or(ContractError::NoPendingMinter {})?;
+    if pending.address != info.sender {
+        return Err(ContractError::Unauthorized {})
     }
 
-    let conf = config.unwrap();
+    if let Some(expiration) = pending.expires {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::Expired {})
+        }
+    }
 
-    let mut total = CONFIG.load(deps.storage)?.token_total;
+    let old_minter = cw721_contract.minter.load(deps.storage)?;
 
-    for attr_values in msg.attributes {
-        let name = format!("{} #{}", conf.name, total);
+    cw721_contract.minter.save(deps.storage, &pending.address)?;
+    PENDING_MINTER.remove(deps.storage);
 
-        let mut attr : Vec<Trait> = vec![];
-        for (index, value) in attr_values.iter().enumerate() {
-            attr.push(Trait {
-                display_type: None,
-                value: value.clone(),
-                trait_type: conf.attributes[index].clone()
-            })
-        }
+    HANDOVER.save(deps.storage, &old_minter)?;
+    let (moved, done) = __handover_stored(deps.storage, &cw721_contract, DEFAULT_SWEEP_LIMIT as usize)?;
 
-        let token = TokenInfo {
-            owner: minter.clone(),
-            approvals: vec![],
-            token_uri: None,
-            extension: Some(Metadata {
-                name: Some(name.clone()),
-                description: Some(format!("{}", conf.desc)),
-                image: Some(format!("{}/{}.png", conf.ipfs, total)),
-                attributes: Some(attr),
-                animation_url: None,
-                background_color: None,
-                image_data: None,
-                external_url: None,
-                youtube_url: None,
-            })
-        };
+    Ok(Response::new()
+        .add_attribute("action", "accept_minter")
+        .add_event(Event::new("minter_transfer_accepted")
+            .add_attribute("old_minter", old_minter)
+            .add_attribute("minter", pending.address)
+            .add_attribute("stored_tokens", moved.to_string())
+            .add_attribute("handover_done", done.to_string())
+        )
+    )
+}
 
-        cw721_contract.tokens.save(deps.storage, &total.to_string(), &token)?;
+// hands the next page of stored tokens over to the new minter, until the previous minter owns none
+pub fn execute_handover_stored(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_update(&deps, &info)?;
 
-        total += Uint128::from(1u8)
-    }
+    let cw721_contract = CW721Contract::default();
+    let from = HANDOVER.may_load(deps.storage)?.ok_or(ContractError::NoHandover {})?;
 
-    __update_total(deps.storage, total)?;
+    let limit = limit.unwrap_or(DEFAULT_SWEEP_LIMIT).min(MAX_SWEEP_LIMIT) as usize;
+    let (moved, done) = __handover_stored(deps.storage, &cw721_contract, limit)?;
 
     Ok(Response::new()
-        .add_attribute("action", "store_conf")
-        .add_attribute("token_total", total.to_string())
+        .add_attribute("action", "handover_stored")
+        .add_attribute("from", from)
+        .add_attribute("moved", moved.to_string())
+        .add_attribute("done", done.to_string())
     )
 }
+
+// cw721 TransferNft, behind the handover check
+pub fn execute_transfer_nft(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let cw721_contract = CW721Contract::default();
+    _can_transfer(deps.storage, &cw721_contract, &token_id)?;
+
+    Ok(cw721_contract.transfer_nft(deps, env, info, recipient, token_id)?)
+}
+
+// cw721 SendNft, behind the handover check
+pub fn execute_send_nft(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let cw721_contract = CW721Contract::default();
+    _can_transfer(deps.storage, &cw721_contract, &token_id)?;
+
+    Ok(cw721_contract.send_nft(deps, env, info, contract, token_id, msg)?)
+}
+
+pub fn execute_cancel_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_update(&deps, &info)?;
+
+    let pending = PENDING_MINTER.may_load(deps.storage)?.ok_or(ContractError::NoPendingMinter {})?;
+    PENDING_MINTER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_minter")
+        .add_event(Event::new("minter_transfer_cancelled")
+            .add_attribute("minter", info.sender)
+            .add_attribute("pending_minter", pending.address)
+        )
+    )
+}
+
+// gives up the minter rights for good, after this every admin action fails and the collection is immutable.
+// nobody can unpause after it, so it is refused while something is paused, and the granted roles go with it
+pub fn execute_renounce_minter(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_update(&deps, &info)?;
+
+    for target in PauseTarget::all() {
+        _not_paused(deps.storage, &env.block.time, target.clone())?;
+        // the pauses left are expired ones
+        PAUSED.remove(deps.storage, target.as_str());
+    }
+
+    let mut revoked = 0u32;
+    for role in Role::all() {
+        let holders = ROLES
+            .prefix(role.as_str())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        for holder in holders {
+            ROLES.remove(deps.storage, (role.as_str(), &holder));
+            revoked += 1;
+        }
+    }
+
+    MINTER_RENOUNCED.save(deps.storage, &true)?;
+    PENDING_MINTER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "renounce_minter")
+        .add_attribute("roles_revoked", revoked.to_string())
+        .add_event(Event::new("minter_renounced")
+            .add_attribute("minter", info.sender)
+        )
+    )
+}
+
+// halts one kind of action (mint, mint batch, burn or store) until unpaused or until the given time
+pub fn execute_pause(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    target: PauseTarget,
+    reason: Option<String>,
+    until: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_role(&deps, &info, Role::Pauser)?;
+
+    if let Some(until) = until {
+        if until <= env.block.time {
+            return Err(ContractError::Expired {})
+        }
+    }
+
+    PAUSED.save(deps.storage, target.as_str(), &PauseInfo {
+        reason: reason.clone(),
+        until,
+        paused_at: env.block.time,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause")
+        .add_attribute("target", target.as_str())
+        .add_attribute("reason", reason.unwrap_or_default())
+        .add_attribute("until", _fmt_time(&until))
+    )
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    target: PauseTarget,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_role(&deps, &info, Role::Pauser)?;
+
+    PAUSED.remove(deps.storage, target.as_str());
+
+    Ok(Response::new()
+        .add_attribute("action", "unpause")
+        .add_attribute("target", target.as_str())
+    )
+}
+
+// gives stored tokens away without payment, either the requested token id or the next available one.
+// airdrops use up the reserved allocation first, once it is used they take from the public supply
+pub fn execute_airdrop(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    drops: Vec<AirdropMsg>,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Mint)?;
+
+    // validate sender permissions
+    _can_update(&deps, &info)?;
+
+    if drops.is_empty() {
+        return Err(ContractError::RequestTooSmall { size: 0 })
+    }
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut ids: Vec<String> = vec![];
+    let mut events: Vec<Event> = vec![];
+    let free = Coin::new(0, config.cost_denom.clone());
+
+    for drop in drops {
+        let recipient = deps.api.addr_validate(&drop.recipient)?;
+        if recipient == minter {
+            return Err(ContractError::Unauthorized {})
+        }
+
+        let token_id = match drop.token_id {
+            Some(token_id) => {
+                if !AVAILABLE_IDX.has(deps.storage, &token_id) {
+                    return Err(ContractError::Claimed {})
+                }
+                // series tokens are minted from their series, with its own supply and schedule
+                if TOKEN_SERIES.has(deps.storage, &token_id) {
+                    return Err(ContractError::SeriesToken { token_id })
+                }
+                token_id
+            },
+            None => _next_available(deps.storage)?,
+        };
+
+        _try_mint(
+            deps.storage,
+            &recipient,
+            &minter,
+            &cw721_contract,
+            &token_id
+        )?;
+
+        events.push(_mint_event(&token_id, &recipient, &free, PHASE_AIRDROP));
+        ids.push(format!("{}:{}", token_id, recipient));
+    }
+
+    let dropped = Uint128::from(ids.len() as u128);
+    let used = RESERVE_USED.may_load(deps.storage)?.unwrap_or_default();
+    let used = (used + dropped).min(config.reserved);
+    RESERVE_USED.save(deps.storage, &used)?;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "airdrop")
+        .add_attribute("dropped", dropped.to_string())
+        .add_attribute("list", String::from(format!("[{}]", ids.join(","))))
+        .add_attribute("reserve_remaining", config.reserved.saturating_sub(used).to_string())
+        .add_events(events)
+    )
+}
+
+// burns every stored token still owned by the minter, one page at a time, and lowers
+// token_total and token_supply so the final size of the collection is kept on chain.
+// the tokens of a series whose own sale is still live are left for that sale
+pub fn execute_sweep_unminted(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Burn)?;
+
+    // validate sender permissions
+    _can_role(&deps, &info, Role::Burner)?;
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    if _is_mint_live(&config, &env.block.time) {
+        return Err(ContractError::MintIsLive {})
+    }
+
+    // the tokens still owned by the previous minter would be left out of the final supply
+    _not_handing_over(deps.storage)?;
+
+    let limit = limit.unwrap_or(DEFAULT_SWEEP_LIMIT).min(MAX_SWEEP_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    // the page is read first and only then burnt, the map can't be mutated while iterating
+    let page: Vec<(String, bool)> = cw721_contract.tokens
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token_id, token)| (token_id, token.owner == minter)))
+        .collect::<StdResult<_>>()?;
+
+    let last = page.last().map(|(token_id, _)| token_id.clone());
+
+    // a series keeps its tokens while its own sale is live, they are swept once it ends
+    let mut swept: Vec<String> = vec![];
+    for (token_id, unminted) in page {
+        if !unminted {
+            continue;
+        }
+        if let Some(series_id) = TOKEN_SERIES.may_load(deps.storage, &token_id)? {
+            let series = SERIES.load(deps.storage, &series_id)?;
+            if _is_mint_live(&_series_config(&config, &series), &env.block.time) {
+                continue;
+            }
+        }
+        swept.push(token_id);
+    }
+
+    let mut events: Vec<Event> = vec![];
+    for token_id in &swept {
+        // the supply of a series shrinks with its swept tokens, retire_token already lowers its stored count
+        if let Some(series_id) = TOKEN_SERIES.may_load(deps.storage, token_id)? {
+            SERIES.update(deps.storage, &series_id, |series| -> Result<_, ContractError> {
+                let mut series = series.ok_or(ContractError::NotFound {})?;
+                series.token_supply = series.token_supply.checked_sub(Uint128::from(1u8))?;
+                Ok(series)
+            })?;
+        }
+
+        let owner = __retire_token(&cw721_contract, deps.storage, token_id.clone())?;
+        events.push(_burn_event(token_id, &owner));
+    }
+
+    // retire_token already took the swept tokens out of token_total
+    let amount = Uint128::from(swept.len() as u128);
+    let mut config = CONFIG.load(deps.storage)?;
+    config.token_supply = config.token_supply.checked_sub(amount)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sweep_unminted")
+        .add_attribute("swept", amount.to_string())
+        .add_attribute("tokens", String::from(format!("[{}]", swept.join(","))))
+        .add_attribute("last_token_id", last.unwrap_or_default())
+        .add_attribute("token_total", config.token_total.to_string())
+        .add_attribute("token_supply", config.token_supply.to_string())
+        .add_events(events)
+    )
+}
+
+// partner holders and voucher owners can mint from the gated phase start, everyone else from start_mint
+fn _start_mint_for(config: &Config, gated: bool) -> Option<Timestamp> {
+    if gated {
+        config.gated_mint.or(config.start_mint)
+    } else {
+        config.start_mint
+    }
+}
+
+// the payer (sender) can mint straight to another address, the limits apply to the recipient.
+// holders of a partner collection can mint during the gated phase and pay the discounted price
+pub fn execute_mint(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: SingleMintMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Mint)?;
+
+    let recipient = match &msg.recipient {
+        Some(recipient) => deps.api.addr_validate(recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
+    let config = CONFIG.load(deps.storage)?;
+    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER ADDRESS
+    let current_count = _minted_count(deps.storage)?; ANDRE: LOADS THE COUNT OF ALREADY MINTED TOKENS
+
+    // a series mints from its own tokens, with its own supply, schedule and price
+    let series = match &msg.series {
+        Some(series_id) => Some(SERIES.load(deps.storage, series_id)?),
+        None => None,
+    };
+
+    let (config, current_count) = match &series {
+        Some(series) => (_series_config(&config, series), series.minted),
+        None => (config, current_count),
+    };
+
+    if msg.partner.is_some() && msg.voucher.is_some() {
+        return Err(ContractError::InvalidVoucher { reason: "partner".to_string() })
+    }
+
+    let partner = match &msg.partner {
+        Some(proof) => Some(_can_use_partner(deps.as_ref(), &config, &recipient, proof, 1)?),
+        None => None,
+    };
+
+    if let Some(voucher) = &msg.voucher {
+        _can_use_voucher(deps.as_ref(), &env, &config, &recipient, voucher, 1)?;
+    }
+
+    // check if we can mint
+    ANDRE: THIS FUNCTION VALIDATES THAT A MINT CAN HAPPEN
+    _can_mint(
+        &current_count,
+        &env.block.time,
+        &_start_mint_for(&config, partner.is_some() || msg.voucher.is_some()),
+        &config.end_mint,
+        config.token_total,
+        config.token_supply,
+        &minter,
+        &recipient
+    )?;
+
+    // the team reserve can't be minted by the public
+    _can_mint_public(deps.storage, &config, msg.series.as_deref(), Uint128::from(1u32))?;
+    _can_mint_wallet(deps.storage, &config, &recipient, Uint128::from(1u32))?;
+    let token_id = match &msg.series {
+        Some(series_id) => _next_series_available(deps.storage, series_id)?,
+        None => _next_available(deps.storage)?,
+    };
+
+    // validate funds according to set price, a voucher brings its own price
+    let price = match &msg.voucher {
+        Some(voucher) => voucher.voucher.price,
+        None => _mint_price(&config, partner.as_ref()),
+    };
+    let coin_found = _can_pay_at(&config, &info, Uint128::from(1u32), price)?; VALIDATES THAT THE EXECUTER HAVE FUNDS TO PAY THE MINT
+
+    ANDRE: TRY TO EXECTUTE THE MINT
+    _try_mint(
+        deps.storage,
+        &recipient,
+        &minter,
+        &cw721_contract,
+        &token_id
+    )?;
+
+    __update_minted(deps.storage, &recipient, Uint128::from(1u32))?;
+
+    if let (Some(proof), Some(partner)) = (&msg.partner, &partner) {
+        __use_partner(deps.storage, &partner.address, &proof.token_id, 1)?;
+    }
+
+    if let Some(voucher) = &msg.voucher {
+        __use_voucher(deps.storage, voucher.voucher.nonce)?;
+    }
+
+    if let (Some(series_id), Some(mut series)) = (&msg.series, series) {
+        series.minted += 1;
+        SERIES.save(deps.storage, series_id, &series)?;
+    }
+
+    _assert_supply(deps.storage)?;
+
+    // send funds to the configured funds wallet
+    // send the info below
+    Ok(Response::new()
+        .add_attribute("action", "mint")
+        .add_attribute("owner", recipient.to_string())
+        .add_attribute("payer", info.sender)
+        .add_attribute("token_id", token_id.clone())
+        .add_attribute("price", price.to_string())
+        .add_attribute("series", msg.series.clone().unwrap_or_else(|| "None".to_string()))
+        .add_event(_mint_event(&token_id, &recipient, &coin_found, _mint_phase(&config, &env.block.time)))
+        .add_messages(_payment_msgs(&config, &coin_found))
+    )
+}
+
+// the amount a batch mint really mints, clamped to 1..=max_mint_batch. the mint simulation uses it too
+fn _mint_amount(config: &Config, amount: Uint128) -> Uint128 {
+    amount.max(Uint128::from(1u32)).min(config.max_mint_batch)
+}
+
+pub fn execute_mint_batch(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: BatchMintMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::MintBatch)?;
+
+    let recipient = match &msg.recipient {
+        Some(recipient) => deps.api.addr_validate(recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let minted_total = _minted_count(deps.storage)?; ANDRE: LOADS THE CURRENT MINT TOTAL
+    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER DEFINED IN THE CONTRACT
+
+    // a series mints from its own tokens, with its own supply, schedule and price
+    let series = match &msg.series {
+        Some(series_id) => Some(SERIES.load(deps.storage, series_id)?),
+        None => None,
+    };
+
+    let (config, minted_total) = match &series {
+        Some(series) => (_series_config(&config, series), series.minted),
+        None => (config, minted_total),
+    };
+
+    let mint_amount = _mint_amount(&config, msg.amount);
+
+    if msg.partner.is_some() && msg.voucher.is_some() {
+        return Err(ContractError::InvalidVoucher { reason: "partner".to_string() })
+    }
+
+    let partner = match &msg.partner {
+        Some(proof) => Some(_can_use_partner(deps.as_ref(), &config, &recipient, proof, mint_amount.u128() as u32)?),
+        None => None,
+    };
+
+    if let Some(voucher) = &msg.voucher {
+        _can_use_voucher(deps.as_ref(), &env, &config, &recipient, voucher, mint_amount.u128() as u32)?;
+    }
+
+    // check if we can mint
+    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
+    _can_mint(
+        &minted_total,
+        &env.block.time,
+        &_start_mint_for(&config, partner.is_some() || msg.voucher.is_some()),
+        &config.end_mint,
+        config.token_total,
+        config.token_supply,
+        &minter,
+        &recipient
+    )?;
+
+    // the team reserve can't be minted by the public
+    _can_mint_public(deps.storage, &config, msg.series.as_deref(), mint_amount)?;
+    _can_mint_wallet(deps.storage, &config, &recipient, mint_amount)?;
+
+    // validate funds according to set price and total to mint
+    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
+    let price = match &msg.voucher {
+        Some(voucher) => voucher.voucher.price,
+        None => _mint_price(&config, partner.as_ref()),
+    };
+    let mut coin_found = _can_pay_at(&config, &info, mint_amount, price)?;
+
+    ANDRE: VARIABLE CREATED TO KEEP TRACK OF TOTAL MINTED TOKENS
+    let mut total_minted = 0u32;
+
+    ANDRE: VECTOR THAT WILL CONTAIN THE IDS OF MINTED TOKENS, TO BE USED IN THE RESPONSE
+    let mut ids: Vec<String> = vec![];
+
+    ANDRE: WHILE LOOP THAT WILL MINT AND UPDATE THE TRACKING VARIABLES STATE
+    while Uint128::from(total_minted) < mint_amount {
+        let token_id = match &msg.series {
+            Some(series_id) => _next_series_available(deps.storage, series_id)?,
+            None => _next_available(deps.storage)?,
+        };
+
+        ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
+        _try_mint(
+            deps.storage,
+            &recipient,
+            &minter,
+            &cw721_contract,
+            &token_id
+        )?;
+
+        total_minted += 1;
+        ids.push(token_id)
+    }
+
+    __update_minted(deps.storage, &recipient, Uint128::from(total_minted))?;
+
+    if let (Some(proof), Some(partner)) = (&msg.partner, &partner) {
+        __use_partner(deps.storage, &partner.address, &proof.token_id, total_minted)?;
+    }
+
+    if let Some(voucher) = &msg.voucher {
+        __use_voucher(deps.storage, voucher.voucher.nonce)?;
+    }
+
+    if let (Some(series_id), Some(mut series)) = (&msg.series, series) {
+        series.minted += u64::from(total_minted);
+        SERIES.save(deps.storage, series_id, &series)?;
+    }
+
+    ANDRE: WHY DO WE NEED TO UPDATE THIS VARIABLE??? tHE RESPONSE FORM THE _can_pay() SHOULD ALREADY CONTAIN THE CORRECT VALUE
+    coin_found.amount = price * Uint128::from(total_minted);
+
+    let unit_price = Coin::new(price.u128(), coin_found.denom.clone());
+    let phase = _mint_phase(&config, &env.block.time);
+    let events: Vec<Event> = ids
+        .iter()
+        .map(|token_id| _mint_event(token_id, &recipient, &unit_price, phase))
+        .collect();
+
+    _assert_supply(deps.storage)?;
+
+    // send funds to the configured funds wallet
+    // send the info below
+    Ok(Response::new()
+        .add_attribute("action", "mint_batch")
+        .add_attribute("owner", recipient.to_string())
+        .add_attribute("payer", info.sender)
+        .add_attribute("requested", msg.amount.to_string())
+        .add_attribute("minted", total_minted.to_string())
+        .add_attribute("cost", coin_found.amount.to_string())
+        .add_attribute("list", String::from(format!("[{}]", ids.join(","))))
+        .add_attribute("series", msg.series.clone().unwrap_or_else(|| "None".to_string()))
+        .add_events(events)
+        .add_messages(_payment_msgs(&config, &coin_found))
+    )
+}
+
+// stores and mints a token in one go, from a MintMsg signed off-chain by the creator.
+// the buyer pays the configured price and the token never sits in the available index
+pub fn execute_lazy_mint(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: LazyMintMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Mint)?;
+
+    let recipient = match &msg.recipient {
+        Some(recipient) => deps.api.addr_validate(recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let cw721_contract = CW721Contract::default();
+    let config = CONFIG.load(deps.storage)?;
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    _can_lazy_mint(deps.as_ref(), &env, &config, &cw721_contract, &msg)?;
+
+    if config.token_total >= config.token_supply {
+        return Err(ContractError::MaxTokenSupply {})
+    }
+
+    _can_mint_wallet(deps.storage, &config, &recipient, Uint128::from(1u32))?;
+    let coin_found = _can_pay(&config, &info, Uint128::from(1u32))?;
+
+    let token_id = msg.nft.token_id.clone();
+    _try_store(deps.storage, &msg.nft, &minter, &cw721_contract)?;
+    __update_total(deps.storage, Uint128::from(1u8))?;
+
+    // check if we can mint, with the new token already counted in the total
+    let config = CONFIG.load(deps.storage)?;
+    _can_mint(
+        &_minted_count(deps.storage)?,
+        &env.block.time,
+        &config.start_mint,
+        &config.end_mint,
+        config.token_total,
+        config.token_supply,
+        &minter,
+        &recipient
+    )?;
+
+    _try_mint(
+        deps.storage,
+        &recipient,
+        &minter,
+        &cw721_contract,
+        &token_id
+    )?;
+
+    __update_minted(deps.storage, &recipient, Uint128::from(1u32))?;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lazy_mint")
+        .add_attribute("owner", recipient.to_string())
+        .add_attribute("payer", info.sender)
+        .add_attribute("token_id", token_id.clone())
+        .add_attribute("token_total", config.token_total.to_string())
+        .add_event(_store_event(&token_id, &minter))
+        .add_event(_mint_event(&token_id, &recipient, &coin_found, PHASE_LAZY))
+        .add_messages(_payment_msgs(&config, &coin_found))
+    )
+}
+
+ANDRE: THIS FUNCTION STORES THE NFT ON CHAIN - I THINK THIS IS A PRE MINT STEP. NOT SURE...
+pub fn execute_store(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    nft_data: MintMsg<Extension>,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
+    _can_store(&deps, &info)?;
+
+    let cw721_contract = CW721Contract::default(); ANDRE: LOADS THE CW721 INTERFACE TO HANDLE OPERATIONS??
+    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOADS THE MINTER DEFINED IN THE CONTRACT
+
+    ANDRE: THIS FUNCTION IS PART OF helpers.rs FILE, THAT WILL BE ANALISED IN A FUTURE CODE JOURNAL
+    _try_store(deps.storage, &nft_data, &minter, &cw721_contract)?;
+
+    // update_total adds the amount on top of the current total
+    __update_total(deps.storage, Uint128::from(1u8))?;
+    let total = CONFIG.load(deps.storage)?.token_total;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "store")
+        .add_attribute("token_total", total.to_string())
+        .add_event(_store_event(&nft_data.token_id, &minter))
+    )
+}
+
+NDRE: THIS FUNCTION STORES A BATCH OF NFTs ON CHAIN - I THINK THIS IS A PRE MINT STEP. NOT SURE...
+pub fn execute_store_batch(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    data: BatchStoreMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    _can_store(&deps, &info)?;
+
+    let cw721_contract = CW721Contract::default(); ANDRE: CREATES THE INTERFACE
+    let minter = cw721_contract.minter.load(deps.storage)?; ANDRE: LOAD THE MINTER INFORMATION
+
+    let stored = Uint128::from(data.batch.len() as u128);
+    let mut events: Vec<Event> = vec![];
+    ANDRE: THIS FUNCTION IS VERY SIMILAR TO THE PREVIOUS ONE, EXCEPT THIS FOR LOOP
+    for nft_data in data.batch {
+        _try_store(deps.storage, &nft_data, &minter, &cw721_contract)?;
+        events.push(_store_event(&nft_data.token_id, &minter));
+    }
+
+    // update_total adds the amount on top of the current total
+    __update_total(deps.storage, stored)?;
+    let total = CONFIG.load(deps.storage)?.token_total;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "store_batch")
+        .add_attribute("token_total", total.to_string())
+        .add_events(events)
+    )
+}
+
+pub fn execute_store_conf(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: StoreConfMsg,
+)-> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    _can_store(&deps, &info)?;
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    // under a series the rows use the store_conf of the series and are numbered inside the series
+    let mut series = match &msg.series {
+        Some(series_id) => Some(SERIES.load(deps.storage, series_id)?),
+        None => None,
+    };
+
+    let mut config = match &series {
+        Some(series) => Some(series.store_conf.clone()),
+        None => CONFIG.load(deps.storage)?.store_conf,
+    };
+    if config.is_none() && msg.conf.is_none() {
+        return Err(ContractError::NoConfiguration {})
+    }
+
+    // only a new conf is validated, the saved one may predate the uri checks and must keep working
+    if let Some(conf) = &msg.conf {
+        _validate_store_conf(conf)?;
+    }
+
+    if msg.conf.is_some() {
+        config = msg.conf
+    }
+
+    let conf = config.unwrap();
+
+    let stored = Uint128::from(msg.attributes.len() as u128);
+    // the rendered id, a series numbers from its own sequence, its stored count goes down when tokens are retired
+    let mut total = match &series {
+        Some(series) => series.conf_seq,
+        None => _next_conf_id(deps.storage)?,
+    };
+
+    // the ids of a series start again at 0, so by default they carry the name of the series
+    let token_id_template = match (&conf.token_id_template, &series) {
+        (Some(template), _) => template.clone(),
+        (None, Some(_)) => DEFAULT_SERIES_TOKEN_ID_TEMPLATE.to_string(),
+        (None, None) => DEFAULT_TOKEN_ID_TEMPLATE.to_string(),
+    };
+
+    // series tokens count in the collection total too, a series can't store past the collection supply
+    let collection = CONFIG.load(deps.storage)?;
+    if collection.token_total + stored > collection.token_supply {
+        return Err(ContractError::MaxTokenSupply {})
+    }
+
+    if let Some(series) = &series {
+        if series.stored + stored > series.token_supply {
+            return Err(ContractError::MaxTokenSupply {})
+        }
+    }
+    let mut events: Vec<Event> = vec![];
+
+    // optional templates stay None when not configured
+    let render = |template: &Option<String>, id: Uint128| {
+        template.as_ref().map(|t| _render_template(t, &conf, id))
+    };
+
+    // every row is validated before anything is saved
+    let columns = _store_columns(&conf);
+    let rows: Vec<Vec<Trait>> = msg.attributes
+        .iter()
+        .enumerate()
+        .map(|(row, values)| _build_traits(&columns, row, values))
+        .collect::<Result<_, ContractError>>()?;
+    let version = __save_store_conf(deps.storage, &conf)?;
+
+    for attr in rows {
+        let token_id = _render_template(&token_id_template, &conf, total);
+        let name = _render_template(conf.name_template.as_deref().unwrap_or(DEFAULT_NAME_TEMPLATE), &conf, total);
+        let image = _render_template(conf.image_template.as_deref().unwrap_or(DEFAULT_IMAGE_TEMPLATE), &conf, total);
+
+        let token = TokenInfo {
+            owner: minter.clone(),
+            approvals: vec![],
+            token_uri: render(&conf.token_uri_template, total),
+            extension: Some(Metadata {
+                name: Some(name.clone()),
+                description: Some(format!("{}", conf.desc)),
+                image: Some(image),
+                attributes: Some(attr),
+                animation_url: render(&conf.animation_url_template, total),
+                background_color: None,
+                image_data: None,
+                external_url: render(&conf.external_url_template, total),
+                youtube_url: None,
+            })
+        };
+
+        _can_edit_metadata(deps.storage, &token_id)?;
+        _not_edition_id(deps.storage, &token_id)?;
+
+        if cw721_contract.tokens.has(deps.storage, &token_id) {
+            return Err(ContractError::Claimed {})
+        }
+
+        cw721_contract.tokens.save(deps.storage, &token_id, &token)?;
+        match &msg.series {
+            Some(series_id) => __push_series(deps.storage, series_id, &token_id)?,
+            None => __push_available(deps.storage, &token_id)?,
+        }
+        __record_provenance(deps.storage, &token_id, &token.token_uri, &token.extension)?;
+        TOKEN_CONF.save(deps.storage, &token_id, &version)?;
+        __index_traits(deps.storage, &token_id, &token.extension)?;
+        events.push(_store_event(&token_id, &minter));
+
+        total += Uint128::from(1u8)
+    }
+
+    // update_total adds the amount on top of the current total
+    __update_total(deps.storage, stored)?;
+
+    match (&msg.series, series.as_mut()) {
+        (Some(series_id), Some(series)) => {
+            series.conf_seq = total;
+            series.stored += stored;
+            series.store_conf = conf.clone();
+            SERIES.save(deps.storage, series_id, series)?;
+        },
+        _ => CONF_SEQ.save(deps.storage, &total)?,
+    }
+
+    let token_total = CONFIG.load(deps.storage)?.token_total;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "store_conf")
+        .add_attribute("series", msg.series.clone().unwrap_or_else(|| "None".to_string()))
+        .add_attribute("token_total", token_total.to_string())
+        .add_events(events)
+    )
+}
+
+// fixes the metadata of a stored token before it is minted
+pub fn execute_update_stored(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    metadata: Metadata,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    _can_role(&deps, &info, Role::StoreAdmin)?;
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    _can_edit_metadata(deps.storage, &token_id)?;
+
+    let mut token = cw721_contract.tokens.load(deps.storage, &token_id)?;
+    if token.owner != minter {
+        return Err(ContractError::Claimed {})
+    }
+
+    // the same rules the row had to pass when it was stored, only the uris for a token stored by hand
+    _validate_metadata(&_token_store_conf(deps.storage, &token_id)?, &metadata)?;
+
+    __unindex_traits(deps.storage, &token_id, &token.extension)?;
+    token.extension = Some(metadata);
+    __index_traits(deps.storage, &token_id, &token.extension)?;
+    __record_provenance(deps.storage, &token_id, &token.token_uri, &token.extension)?;
+
+    cw721_contract.tokens.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_stored")
+        .add_attribute("token_id", token_id.clone())
+        .add_event(_store_event(&token_id, &minter))
+    )
+}
+
+// takes stored tokens out of the collection before they are minted, all or none
+pub fn execute_remove_stored(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    _can_role(&deps, &info, Role::StoreAdmin)?;
+
+    if token_ids.is_empty() {
+        return Err(ContractError::RequestTooSmall { size: 0 })
+    }
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    // every token must exist and still be owned by the minter
+    _can_burn_batch(deps.storage, &cw721_contract, &token_ids, Some(&minter))?;
+
+    for token_id in &token_ids {
+        _can_edit_metadata(deps.storage, token_id)?;
+    }
+
+    let mut events: Vec<Event> = vec![];
+    for token_id in &token_ids {
+        // retire_token lowers token_total and takes the token out of the available and trait indexes
+        let owner = __retire_token(&cw721_contract, deps.storage, token_id.clone())?;
+        events.push(_burn_event(token_id, &owner));
+    }
+
+    _assert_supply(deps.storage)?;
+
+    let total = CONFIG.load(deps.storage)?.token_total;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_stored")
+        .add_attribute("tokens", String::from(format!("[{}]", token_ids.join(","))))
+        .add_attribute("token_total", total.to_string())
+        .add_events(events)
+    )
+}
+
+// freezes the metadata for good, for the whole collection or only for the given id ranges (inclusive).
+// a range with a prefix covers the ids {prefix}{start} to {prefix}{end}, ex: "evolved-" 1 to 10.
+// there is no unfreeze, after this the art behind the frozen tokens can't be swapped
+pub fn execute_freeze_metadata(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    ranges: Option<Vec<FrozenRange>>,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_update(&deps, &info)?;
+
+    let mut freeze = METADATA_FREEZE.may_load(deps.storage)?.unwrap_or(MetadataFreeze {
+        all: None,
+        ranges: vec![],
+    });
+
+    if freeze.all.is_some() {
+        return Err(ContractError::MetadataFrozen { token_id: "*".to_string() })
+    }
+
+    let scope = match ranges {
+        None => {
+            freeze.all = Some(env.block.height);
+            "all".to_string()
+        },
+        Some(ranges) => {
+            if ranges.is_empty() {
+                return Err(ContractError::RequestTooSmall { size: 0 })
+            }
+
+            let mut scope: Vec<String> = vec![];
+            for range in ranges {
+                if range.start > range.end {
+                    return Err(ContractError::InvalidRange { start: range.start, end: range.end })
+                }
+
+                // an empty prefix is the numeric ids, keep a single spelling for it
+                let range = FrozenRange {
+                    prefix: range.prefix.filter(|prefix| !prefix.is_empty()),
+                    ..range
+                };
+
+                let prefix = range.prefix.clone().unwrap_or_default();
+                scope.push(format!("{}{}-{}{}", prefix, range.start, prefix, range.end));
+                freeze.ranges.push(FrozenRange { height: Some(env.block.height), ..range });
+            }
+            String::from(format!("[{}]", scope.join(",")))
+        },
+    };
+
+    METADATA_FREEZE.save(deps.storage, &freeze)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "freeze_metadata")
+        .add_attribute("scope", scope)
+        .add_attribute("height", env.block.height.to_string())
+    )
+}
+
+// changes traits of a minted token, ex: the level after a game event. only the traits marked mutable
+// in the store_conf schema can change, and every change is kept in the trait history of the token
+pub fn execute_upgrade_traits(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    traits: Vec<Trait>,
+    consent: Option<Binary>,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_role(&deps, &info, Role::Upgrader)?;
+
+    _can_upgrade_traits(&_token_schema_conf(deps.storage, &token_id)?, &traits)?;
+
+    _can_edit_metadata(deps.storage, &token_id)?;
+
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    // stored tokens are fixed with UpdateStored, upgrades are for the tokens out in the wild
+    let mut token = cw721_contract.tokens.load(deps.storage, &token_id)?;
+    if token.owner == minter {
+        return Err(ContractError::NotMinted { token_id })
+    }
+
+    let seq = _next_upgrade_seq(deps.storage, &token_id)?;
+    let payload = UpgradePayload {
+        token_id: token_id.clone(),
+        traits: traits.clone(),
+        seq,
+    };
+    let consented = _can_upgrade_with_consent(deps.as_ref(), &env, &token.owner, &payload, &consent)?;
+
+    __unindex_traits(deps.storage, &token_id, &token.extension)?;
+    let before = __apply_traits(&mut token.extension, &traits);
+    __index_traits(deps.storage, &token_id, &token.extension)?;
+
+    cw721_contract.tokens.save(deps.storage, &token_id, &token)?;
+
+    let changes: Vec<TraitUpdate> = traits
+        .into_iter()
+        .zip(before.into_iter())
+        .map(|(attr, before)| TraitUpdate {
+            trait_type: attr.trait_type,
+            before,
+            after: attr.value,
+        })
+        .collect();
+
+    let summary: Vec<String> = changes
+        .iter()
+        .map(|c| format!("{}:{}->{}", c.trait_type, c.before.as_deref().unwrap_or("None"), c.after))
+        .collect();
+
+    TRAIT_HISTORY.save(deps.storage, (&token_id, seq), &TraitChange {
+        seq,
+        changes,
+        upgrader: info.sender.clone(),
+        height: env.block.height,
+        time: env.block.time,
+        consent: consented,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "upgrade_traits")
+        .add_attribute("token_id", token_id)
+        .add_attribute("owner", token.owner.to_string())
+        .add_attribute("upgrader", info.sender.to_string())
+        .add_attribute("seq", seq.to_string())
+        .add_attribute("changes", String::from(format!("[{}]", summary.join(","))))
+        .add_attribute("consent", consented.to_string())
+    )
+}
+
+// an owner that sets a key must sign every upgrade of their tokens, None goes back to no consent
+pub fn execute_set_upgrade_consent(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let response = Response::new()
+        .add_attribute("action", "set_upgrade_consent")
+        .add_attribute("owner", info.sender.to_string());
+
+    match pubkey {
+        Some(pubkey) => {
+            _validate_pubkey(&pubkey)?;
+            UPGRADE_CONSENT.save(deps.storage, &info.sender, &pubkey)?;
+            Ok(response.add_attribute("pubkey", pubkey.to_base64()))
+        },
+        None => {
+            UPGRADE_CONSENT.remove(deps.storage, &info.sender);
+            Ok(response.add_attribute("pubkey", "None"))
+        },
+    }
+}
+
+// the owner burns the inputs and gets a new token with the traits derived by config.evolve.
+// the new token keeps the uri and metadata of the first input, only the traits change
+pub fn execute_evolve(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Burn)?;
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Mint)?;
+
+    let cw721_contract = CW721Contract::default();
+    let config = CONFIG.load(deps.storage)?;
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    let rules = config.evolve.clone().ok_or(ContractError::EvolveDisabled {})?;
+
+    if token_ids.len() != rules.inputs as usize {
+        return Err(ContractError::InvalidEvolve { reason: format!("expected {} tokens, got {}", rules.inputs, token_ids.len()) })
+    }
+
+    let unique: HashSet<&String> = token_ids.iter().collect();
+    if unique.len() != token_ids.len() {
+        return Err(ContractError::InvalidEvolve { reason: "duplicated token".to_string() })
+    }
+
+    // stored tokens are not evolved, only minted ones
+    if info.sender == minter {
+        return Err(ContractError::Unauthorized {})
+    }
+
+    // every input must belong to the sender, all or none
+    _can_burn_batch(deps.storage, &cw721_contract, &token_ids, Some(&info.sender))?;
+
+    let inputs = token_ids
+        .iter()
+        .map(|token_id| cw721_contract.tokens.load(deps.storage, token_id))
+        .collect::<StdResult<Vec<TokenInfo<Extension>>>>()?;
+
+    // the evolved token is the first input with the new traits, it needs metadata to carry them
+    if inputs[0].extension.is_none() {
+        return Err(ContractError::InvalidEvolve { reason: format!("{} has no metadata", token_ids[0]) })
+    }
+
+    let extensions: Vec<Extension> = inputs.iter().map(|t| t.extension.clone()).collect();
+    let traits = _evolve_traits(&_token_schema_conf(deps.storage, &token_ids[0])?, &rules, &extensions)?;
+    let conf_version = TOKEN_CONF.may_load(deps.storage, &token_ids[0])?;
+
+    let mut events: Vec<Event> = vec![];
+    for token_id in &token_ids {
+        let owner = __burn_token(&cw721_contract, deps.storage, token_id.clone())?;
+
+        __update_burnt_amount(deps.storage, &info.sender)?;
+
+        __update_burnt_list(deps.storage, &info.sender, token_id)?;
+
+        events.push(_burn_event(token_id, &owner));
+    }
+
+    let seq = EVOLVE_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    EVOLVE_SEQ.save(deps.storage, &seq)?;
+
+    let token_id = format!("{}{}", EVOLVED_TOKEN_PREFIX, seq);
+    let mut extension = inputs[0].extension.clone();
+    if let Some(metadata) = extension.as_mut() {
+        metadata.attributes = Some(traits);
+    }
+
+    if cw721_contract.tokens.has(deps.storage, &token_id) || BURNED.has(deps.storage, token_id.clone()) {
+        return Err(ContractError::Claimed {})
+    }
+
+    // the evolved token replaces its inputs, it is never stored so token_total and the available
+    // index are left alone, the ledger counts it apart from the minted tokens
+    let token = TokenInfo {
+        owner: info.sender.clone(),
+        approvals: vec![],
+        token_uri: inputs[0].token_uri.clone(),
+        extension,
+    };
+
+    cw721_contract.tokens.save(deps.storage, &token_id, &token)?;
+    cw721_contract.increment_tokens(deps.storage)?;
+    __index_traits(deps.storage, &token_id, &token.extension)?;
+    __update_supply(deps.storage, |ledger| ledger.evolved += 1)?;
+
+    // the evolved token keeps the schema of the input it was built from
+    if let Some(version) = conf_version {
+        TOKEN_CONF.save(deps.storage, &token_id, &version)?;
+    }
+
+    LINEAGE.save(deps.storage, &token_id, &Lineage {
+        parents: token_ids.clone(),
+        owner: info.sender.clone(),
+        height: env.block.height,
+    })?;
+    for parent in &token_ids {
+        EVOLVED_INTO.save(deps.storage, parent, &token_id)?;
+    }
+
+    _assert_supply(deps.storage)?;
+
+    let price = Coin { denom: config.cost_denom.clone(), amount: Uint128::zero() };
+
+    Ok(Response::new()
+        .add_attribute("action", "evolve")
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("tokens", String::from(format!("[{}]", token_ids.join(","))))
+        .add_attribute("token_id", token_id.clone())
+        .add_events(events)
+        .add_event(_mint_event(&token_id, &info.sender, &price, PHASE_EVOLVE))
+    )
+}
+
+// stores one metadata template that is minted as many times as the cap allows (open edition without a cap).
+// editions are not stored tokens, they don't take from token_total nor from the available index
+pub fn execute_store_edition(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: StoreEditionMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Store)?;
+
+    // validate sender permissions
+    _can_role(&deps, &info, Role::StoreAdmin)?;
+
+    _can_edit_collection(deps.storage)?;
+
+    if EDITIONS.has(deps.storage, &msg.template_id) {
+        return Err(ContractError::Claimed {})
+    }
+
+    let cw721_contract = CW721Contract::default();
+    _validate_edition(&msg.metadata, &msg.token_uri)?;
+    _can_reserve_edition(deps.storage, &cw721_contract, &msg.template_id)?;
+
+    if let (Some(start), Some(end)) = (msg.start, msg.end) {
+        if start >= end {
+            return Err(ContractError::InvalidEdition { reason: "start must be before end".to_string() })
+        }
+    }
+
+    if msg.cap == Some(0) {
+        return Err(ContractError::InvalidEdition { reason: "cap".to_string() })
+    }
+
+    EDITIONS.save(deps.storage, &msg.template_id, &Edition {
+        metadata: msg.metadata,
+        token_uri: msg.token_uri,
+        cap: msg.cap,
+        start: msg.start,
+        end: msg.end,
+        minted: 0,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "store_edition")
+        .add_attribute("template_id", msg.template_id)
+        .add_attribute("cap", msg.cap.map(|c| c.to_string()).unwrap_or_else(|| "None".to_string()))
+        .add_attribute("start", _fmt_time(&msg.start))
+        .add_attribute("end", _fmt_time(&msg.end))
+    )
+}
+
+// mints the next edition of a template, ex: 'template-42' for the 42nd copy
+pub fn execute_mint_edition(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: EditionMintMsg,
+) -> Result<Response, ContractError> {
+    _not_paused(deps.storage, &env.block.time, PauseTarget::Mint)?;
+
+    let recipient = match &msg.recipient {
+        Some(recipient) => deps.api.addr_validate(recipient)?,
+        None => info.sender.clone(),
+    };
+
+    let cw721_contract = CW721Contract::default();
+    let config = CONFIG.load(deps.storage)?;
+    let minter = cw721_contract.minter.load(deps.storage)?;
+
+    let mut edition = EDITIONS.load(deps.storage, &msg.template_id)?;
+
+    // the cap of the template takes the place of token_total and token_supply, no cap means no limit
+    let cap = edition.cap.map(Uint128::from).unwrap_or(Uint128::MAX);
+    _can_mint(
+        &edition.minted,
+        &env.block.time,
+        &edition.start,
+        &edition.end,
+        cap,
+        cap,
+        &minter,
+        &recipient
+    )?;
+
+    _can_mint_wallet(deps.storage, &config, &recipient, Uint128::from(1u32))?;
+    let coin_found = _can_pay(&config, &info, Uint128::from(1u32))?;
+
+    edition.minted += 1;
+    let (token_id, extension) = _edition_token(&msg.template_id, &edition, edition.minted);
+
+    if cw721_contract.tokens.has(deps.storage, &token_id) || BURNED.has(deps.storage, token_id.clone()) {
+        return Err(ContractError::Claimed {})
+    }
+
+    let token = TokenInfo {
+        owner: recipient.clone(),
+        approvals: vec![],
+        token_uri: edition.token_uri.clone(),
+        extension,
+    };
+
+    cw721_contract.tokens.save(deps.storage, &token_id, &token)?;
+    cw721_contract.increment_tokens(deps.storage)?;
+    __index_traits(deps.storage, &token_id, &token.extension)?;
+    __update_supply(deps.storage, |ledger| ledger.editions += 1)?;
+    EDITIONS.save(deps.storage, &msg.template_id, &edition)?;
+
+    __update_minted(deps.storage, &recipient, Uint128::from(1u32))?;
+
+    _assert_supply(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "mint_edition")
+        .add_attribute("owner", recipient.to_string())
+        .add_attribute("payer", info.sender)
+        .add_attribute("template_id", msg.template_id)
+        .add_attribute("edition", edition.minted.to_string())
+        .add_attribute("token_id", token_id.clone())
+        .add_event(_mint_event(&token_id, &recipient, &coin_found, PHASE_EDITION))
+        .add_messages(_payment_msgs(&config, &coin_found))
+    )
+}
+
+// creates a series or changes its settings. a new series needs its store_conf and its supply,
+// the rest starts closed (no schedule) at the price of the collection
+pub fn execute_update_series(
+    env: Env,
+    deps: DepsMut,
+    info: MessageInfo,
+    series_id: String,
+    msg: UpdateSeriesMsg,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_role(&deps, &info, Role::ConfigAdmin)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut series = match SERIES.may_load(deps.storage, &series_id)? {
+        Some(series) => series,
+        None => {
+            let (store_conf, token_supply) = match (&msg.store_conf, msg.token_supply) {
+                (Some(store_conf), Some(token_supply)) => (store_conf.clone(), token_supply),
+                _ => return Err(ContractError::NoConfiguration {}),
+            };
+
+            Series {
+                store_conf,
+                token_supply,
+                stored: Uint128::zero(),
+                conf_seq: Uint128::zero(),
+                minted: 0,
+                cost_amount: config.cost_amount,
+                cost_denom: config.cost_denom.clone(),
+                gated_mint: None,
+                start_mint: None,
+                end_mint: None,
+                revealed: false,
+            }
+        },
+    };
+
+    let live = _is_mint_live(&_series_config(&config, &series), &env.block.time);
+
+    let mut res = Response::new()
+        .add_attribute("action", "update_series")
+        .add_attribute("series", series_id.clone());
+
+    if let Some(store_conf) = msg.store_conf {
+        _validate_store_conf(&store_conf)?;
+
+        res = res.add_attribute("store_conf", store_conf.name.clone());
+        series.store_conf = store_conf;
+    }
+
+    if let Some(token_supply) = msg.token_supply {
+        if token_supply < series.stored {
+            return Err(ContractError::MaxTokenSupply {})
+        }
+
+        res = res
+            .add_attribute("token_supply_before", series.token_supply.to_string())
+            .add_attribute("token_supply_after", token_supply.to_string());
+        series.token_supply = token_supply;
+    }
+
+    // the sale of the series goes through the same checks as the collection one
+    let mut sale = _series_config(&config, &series);
+    res = _update_sale(res, &mut sale, live, SaleUpdate {
+        gated_mint: msg.gated_mint,
+        start_mint: msg.start_mint,
+        end_mint: msg.end_mint,
+        cost_amount: msg.cost_amount,
+        cost_denom: msg.cost_denom,
+    })?;
+
+    series.gated_mint = sale.gated_mint;
+    series.start_mint = sale.start_mint;
+    series.end_mint = sale.end_mint;
+    series.cost_amount = sale.cost_amount;
+    series.cost_denom = sale.cost_denom;
+
+    SERIES.save(deps.storage, &series_id, &series)?;
+
+    Ok(res)
+}
+
+// marks the series as revealed, it can't go back. a collection wide metadata freeze also closes the reveal
+pub fn execute_reveal_series(
+    deps: DepsMut,
+    info: MessageInfo,
+    series_id: String,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_role(&deps, &info, Role::StoreAdmin)?;
+
+    _can_edit_collection(deps.storage)?;
+
+    let mut series = SERIES.load(deps.storage, &series_id)?;
+    if series.revealed {
+        return Err(ContractError::AlreadyRevealed {})
+    }
+
+    series.revealed = true;
+    SERIES.save(deps.storage, &series_id, &series)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal_series")
+        .add_attribute("series", series_id)
+    )
+}
+
+// indexes the traits of the next page of tokens of a collection migrated from before the trait indexes,
+// until every token is counted. tokens stored or changed meanwhile are already indexed and are skipped
+pub fn execute_backfill_traits(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // validate sender permissions
+    _can_update(&deps, &info)?;
+
+    let backfill = TRAIT_BACKFILL.may_load(deps.storage)?.ok_or(ContractError::NoBackfill {})?;
+
+    let cw721_contract = CW721Contract::default();
+    let limit = limit.unwrap_or(DEFAULT_SWEEP_LIMIT).min(MAX_SWEEP_LIMIT) as usize;
+    let start = backfill.start_after.clone().map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let page: Vec<(String, Extension)> = cw721_contract.tokens
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token_id, token)| (token_id, token.extension)))
+        .collect::<StdResult<_>>()?;
+
+    for (token_id, extension) in &page {
+        __index_traits(deps.storage, token_id, extension)?;
+    }
+
+    let done = page.len() < limit;
+    match page.last() {
+        Some((last, _)) if !done => TRAIT_BACKFILL.save(deps.storage, &TraitBackfill { start_after: Some(last.clone()) })?,
+        _ => TRAIT_BACKFILL.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "backfill_traits")
+        .add_attribute("indexed", page.len().to_string())
+        .add_attribute("done", done.to_string())
+    )
+}
+
+// upgrades the state of a live collection in place. the steps run in order, each one only when the
+// stored version is older than the version that introduced the change and the new code has that change.
+// a migrate to the same version runs no step
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut,
+    _env: Env,
+    _msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate { previous_contract: stored.contract })
+    }
+
+    let stored_version: Version = stored.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+
+    // refuse downgrades, the old code would not understand the new state
+    if stored_version > new_version {
+        return Err(ContractError::CannotMigrateDowngrade {
+            previous_version: stored.version,
+            new_version: CONTRACT_VERSION.to_string(),
+        })
+    }
+
+    let mut steps: Vec<&str> = vec![];
+    let step = |major: u64, minor: u64| {
+        let introduced = Version::new(major, minor, 0);
+        stored_version < introduced && new_version >= introduced
+    };
+
+    if step(0, 2) {
+        _migrate_config_v0_2(deps.storage)?;
+        steps.push("config_v0_2");
+    }
+
+    if step(0, 4) {
+        _migrate_available_v0_4(deps.storage)?;
+        steps.push("available_v0_4");
+    }
+
+    if step(0, 5) {
+        _migrate_supply_v0_5(deps.storage)?;
+        steps.push("supply_v0_5");
+
+        _migrate_burns_v0_5(deps.storage)?;
+        steps.push("burns_v0_5");
+    }
+
+    if step(0, 6) {
+        _migrate_config_v0_6(deps.storage)?;
+        steps.push("config_v0_6");
+
+        // the trait indexes are built in pages with BackfillTraits, a single migration could run out of gas
+        TRAIT_BACKFILL.save(deps.storage, &TraitBackfill { start_after: None })?;
+        steps.push("traits_v0_6");
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", new_version.to_string())
+        .add_attribute("steps", String::from(format!("[{}]", steps.join(","))))
+    )
+}
+
+// v0.2 added the burn batch limits and the end of the mint window to the config.
+// it writes the v0.2 shape, the fields added later are filled by the v0.6 step
+fn _migrate_config_v0_2(
+    storage: &mut dyn Storage,
+) -> Result<(), ContractError> {
+    let old = CONFIG_V1.load(storage)?;
+
+    CONFIG_V5.save(storage, &ConfigV5 {
+        owners_can_burn: old.owners_can_burn,
+        minter_can_burn: old.minter_can_burn,
+        start_mint: old.start_mint,
+        end_mint: None,
+        cost_amount: old.cost_amount,
+        cost_denom: old.cost_denom,
+        funds_wallet: old.funds_wallet,
+        token_total: old.token_total,
+        token_supply: old.token_supply,
+        max_mint_batch: old.max_mint_batch,
+        min_burn_batch: Uint128::from(1u32),
+        max_burn_batch: Uint128::from(30u32),
+        reserved: None,
+        max_per_wallet: None,
+        gated_mint: None,
+        partners: None,
+        voucher_signer: None,
+        lazy_signer: None,
+        store_conf: old.store_conf,
+        evolve: None,
+    })?;
+
+    Ok(())
+}
+
+// v0.6 completes the config and the ledger of any older version. CONFIG_V5 and SUPPLY_V5 read the same
+// keys as CONFIG and SUPPLY with every field added after v0.2 optional (reserved, max_per_wallet,
+// gated_mint, partners, voucher_signer, lazy_signer, evolve and the editions count of the ledger)
+fn _migrate_config_v0_6(
+    storage: &mut dyn Storage,
+) -> Result<(), ContractError> {
+    let old = CONFIG_V5.load(storage)?;
+
+    CONFIG.save(storage, &Config {
+        owners_can_burn: old.owners_can_burn,
+        minter_can_burn: old.minter_can_burn,
+        start_mint: old.start_mint,
+        end_mint: old.end_mint,
+        cost_amount: old.cost_amount,
+        cost_denom: old.cost_denom,
+        funds_wallet: old.funds_wallet,
+        token_total: old.token_total,
+        token_supply: old.token_supply,
+        max_mint_batch: old.max_mint_batch,
+        min_burn_batch: old.min_burn_batch,
+        max_burn_batch: old.max_burn_batch,
+        reserved: old.reserved.unwrap_or_default(),
+        max_per_wallet: old.max_per_wallet,
+        gated_mint: old.gated_mint,
+        partners: old.partners.unwrap_or_default(),
+        voucher_signer: old.voucher_signer,
+        lazy_signer: old.lazy_signer,
+        store_conf: old.store_conf,
+        evolve: old.evolve,
+    })?;
+
+    if let Some(old) = SUPPLY_V5.may_load(storage)? {
+        SUPPLY.save(storage, &SupplyLedger {
+            stored: old.stored,
+            minted: old.minted,
+            burnt_before_mint: old.burnt_before_mint,
+            burnt_after_mint: old.burnt_after_mint,
+            editions: old.editions.unwrap_or_default(),
+            evolved: old.evolved.unwrap_or_default(),
+        })?;
+    }
+
+    Ok(())
+}
+
+// v0.4 mints from the available index instead of the token count, so it is built from the
+// stored tokens still owned by the minter
+fn _migrate_available_v0_4(
+    storage: &mut dyn Storage,
+) -> Result<(), ContractError> {
+    let cw721_contract = CW721Contract::default();
+    let minter = cw721_contract.minter.load(storage)?;
+
+    let stored: Vec<String> = cw721_contract.tokens
+        .idx
+        .owner
+        .prefix(minter)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    for token_id in stored {
+        __push_available(storage, &token_id)?;
+    }
+
+    Ok(())
+}
+
+// v0.5 counts the supply in a single ledger. the history of older versions is unknown, so the ledger
+// starts from what is on chain: the minted tokens plus the ones still available
+fn _migrate_supply_v0_5(
+    storage: &mut dyn Storage,
+) -> Result<(), ContractError> {
+    let cw721_contract = CW721Contract::default();
+    let minted = cw721_contract.token_count(storage)?;
+    let available = AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default();
+
+    SUPPLY.save(storage, &SupplyLedger {
+        stored: minted + available,
+        minted,
+        burnt_before_mint: 0,
+        burnt_after_mint: 0,
+        editions: 0,
+        evolved: 0,
+    })?;
+
+    // older versions double counted the total on every store
+    CONFIG_V5.update(storage, |mut config| -> Result<_, ContractError> {
+        config.token_total = Uint128::from(minted + available);
+        Ok(config)
+    })?;
+
+    Ok(())
+}
+
+// the ledger of the supply step starts without burns, this step rebuilds them from the burn maps kept since
+// the first version. before v0.5 every burn lowered the cw721 token_count, also the burns of stored tokens,
+// so the tokens ever minted are the token_count plus every burn. the minted tokens still alive are the live
+// tokens not available anymore, the other burns were burns of stored tokens. token_count is set back to the
+// minted tokens alive, as the ledger counts it from now on
+fn _migrate_burns_v0_5(
+    storage: &mut dyn Storage,
+) -> Result<(), ContractError> {
+    let cw721_contract = CW721Contract::default();
+
+    let burnt: u64 = BURNT_LIST
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, list)| list.len() as u64))
+        .sum::<StdResult<u64>>()?;
+
+    let available = AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default();
+    let live = cw721_contract.tokens
+        .keys(storage, None, None, Order::Ascending)
+        .count() as u64;
+    let alive = live.saturating_sub(available);
+
+    let minted = cw721_contract.token_count(storage)? + burnt;
+    let burnt_after_mint = minted.saturating_sub(alive);
+    let burnt_before_mint = burnt.saturating_sub(burnt_after_mint);
+
+    let ledger = SupplyLedger {
+        stored: minted + available + burnt_before_mint,
+        minted,
+        burnt_before_mint,
+        burnt_after_mint,
+        editions: 0,
+        evolved: 0,
+    };
+    SUPPLY.save(storage, &ledger)?;
+    cw721_contract.token_count.save(storage, &alive)?;
+
+    CONFIG_V5.update(storage, |mut config| -> Result<_, ContractError> {
+        config.token_total = Uint128::from(ledger.stored - ledger.burnt_before_mint);
+        Ok(config)
+    })?;
+
+    Ok(())
+}
+
+pub fn query_roles(
+    deps: Deps,
+) -> StdResult<RolesResponse> {
+    let roles = Role::all()
+        .into_iter()
+        .map(|role| {
+            let holders = ROLES
+                .prefix(role.as_str())
+                .keys(deps.storage, None, None, Order::Ascending)
+                .map(|addr| addr.map(|a| a.to_string()))
+                .collect::<StdResult<Vec<String>>>()?;
+
+            Ok(RoleHolders { role, holders })
+        })
+        .collect::<StdResult<Vec<RoleHolders>>>()?;
+
+    Ok(RolesResponse { roles })
+}
+
+pub fn query_pending_minter(
+    deps: Deps,
+) -> StdResult<PendingMinterResponse> {
+    let pending = PENDING_MINTER.may_load(deps.storage)?;
+
+    Ok(PendingMinterResponse {
+        pending_minter: pending.as_ref().map(|p| p.address.to_string()),
+        expires: pending.and_then(|p| p.expires),
+        renounced: MINTER_RENOUNCED.may_load(deps.storage)?.unwrap_or(false),
+        handover_from: HANDOVER.may_load(deps.storage)?.map(|a| a.to_string()),
+    })
+}
+
+// pauses that already reached their automatic unpause time are reported as not paused
+pub fn query_pause(
+    deps: Deps,
+    env: Env,
+) -> StdResult<PauseResponse> {
+    let paused = PauseTarget::all()
+        .into_iter()
+        .map(|target| {
+            let info = PAUSED
+                .may_load(deps.storage, target.as_str())?
+                .filter(|info| info.until.map_or(true, |until| env.block.time < until));
+
+            Ok(PauseState {
+                target,
+                paused: info.is_some(),
+                reason: info.as_ref().and_then(|i| i.reason.clone()),
+                paused_at: info.as_ref().map(|i| i.paused_at),
+                until: info.and_then(|i| i.until),
+            })
+        })
+        .collect::<StdResult<Vec<PauseState>>>()?;
+
+    Ok(PauseResponse { paused })
+}
+
+pub fn query_reserve(
+    deps: Deps,
+) -> StdResult<ReserveResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let used = RESERVE_USED.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(ReserveResponse {
+        reserved: config.reserved,
+        used,
+        remaining: _reserve_remaining(deps.storage, &config).map_err(|e| StdError::generic_err(e.to_string()))?,
+    })
+}
+
+// runs every check of a public mint for 'address' without stopping at the first failure,
+// so the frontend can explain everything that blocks the mint at once
+pub fn query_simulate_mint(
+    deps: Deps,
+    env: Env,
+    address: String,
+    amount: Uint128,
+    funds: Vec<Coin>,
+    partner: Option<PartnerToken>,
+    voucher: Option<SignedVoucher>,
+    series: Option<String>,
+) -> StdResult<SimulateMintResponse> {
+    let cw721_contract = CW721Contract::default();
+    let config = CONFIG.load(deps.storage)?;
+    let minter = cw721_contract.minter.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    let count = _minted_count(deps.storage)?;
+    let time = env.block.time;
+
+    // a series is simulated against its own supply, schedule and price, like execute_mint does
+    let (config, count) = match &series {
+        Some(series_id) => {
+            let found = SERIES.load(deps.storage, series_id)?;
+            (_series_config(&config, &found), found.minted)
+        },
+        None => (config, count),
+    };
+
+    let mut failures: Vec<String> = vec![];
+
+    let target = if amount <= Uint128::from(1u32) { PauseTarget::Mint } else { PauseTarget::MintBatch };
+    if let Err(e) = _not_paused(deps.storage, &time, target) {
+        failures.push(e.to_string());
+    }
+
+    // the stored tokens are not minted while the previous minter hands them over
+    if let Err(e) = _not_handing_over(deps.storage) {
+        failures.push(e.to_string());
+    }
+
+    // same amount as execute_mint_batch would mint
+    let amount = _mint_amount(&config, amount);
+
+    // partner token or voucher, they open the gated phase and change the price
+    if partner.is_some() && voucher.is_some() {
+        failures.push(ContractError::InvalidVoucher { reason: "partner".to_string() }.to_string());
+    }
+
+    let partner_found = match &partner {
+        Some(proof) => match _can_use_partner(deps, &config, &address, proof, amount.u128() as u32) {
+            Ok(found) => Some(found),
+            Err(e) => {
+                failures.push(e.to_string());
+                None
+            }
+        },
+        None => None,
+    };
+
+    if let Some(voucher) = &voucher {
+        if let Err(e) = _can_use_voucher(deps, &env, &config, &address, voucher, amount.u128() as u32) {
+            failures.push(e.to_string());
+        }
+    }
+
+    // supply, start and end time, the same checks as _can_mint
+    if let Err(e) = _can_mint(
+        &count,
+        &time,
+        &_start_mint_for(&config, partner.is_some() || voucher.is_some()),
+        &config.end_mint,
+        config.token_total,
+        config.token_supply,
+        &minter,
+        &address
+    ) {
+        failures.push(e.to_string());
+    }
+
+    if let Err(e) = _can_mint_public(deps.storage, &config, series.as_deref(), amount) {
+        failures.push(e.to_string());
+    }
+
+    // per wallet limit
+    if let Err(e) = _can_mint_wallet(deps.storage, &config, &address, amount) {
+        failures.push(e.to_string());
+    }
+
+    // payment
+    let price = match &voucher {
+        Some(voucher) => voucher.voucher.price,
+        None => _mint_price(&config, partner_found.as_ref()),
+    };
+    let info = MessageInfo { sender: address, funds };
+    let cost = match _can_pay_at(&config, &info, amount, price) {
+        Ok(coin) => Some(coin),
+        Err(e) => {
+            failures.push(e.to_string());
+            None
+        }
+    };
+
+    if !failures.is_empty() {
+        return Ok(SimulateMintResponse {
+            can_mint: false,
+            failures,
+            cost: None,
+            token_ids: vec![],
+        })
+    }
+
+    let token_ids = match &series {
+        Some(series_id) => _peek_series_available(deps.storage, series_id, amount.u128() as usize),
+        None => _peek_available(deps.storage, amount.u128() as usize),
+    }.map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(SimulateMintResponse {
+        can_mint: true,
+        failures,
+        cost,
+        token_ids,
+    })
+}
+
+pub fn query_supply_info(
+    deps: Deps,
+) -> StdResult<SupplyInfoResponse> {
+    let cw721_contract = CW721Contract::default();
+    let config = CONFIG.load(deps.storage)?;
+    let ledger = SUPPLY.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(SupplyInfoResponse {
+        stored: ledger.stored,
+        minted: ledger.minted,
+        burnt_before_mint: ledger.burnt_before_mint,
+        burnt_after_mint: ledger.burnt_after_mint,
+        remaining: ledger.stored - ledger.minted - ledger.burnt_before_mint,
+        editions: ledger.editions,
+        evolved: ledger.evolved,
+        circulating: ledger.minted + ledger.editions + ledger.evolved - ledger.burnt_after_mint,
+        token_total: config.token_total,
+        token_supply: config.token_supply,
+        token_count: cw721_contract.token_count(deps.storage)?,
+    })
+}
+
+fn _frequency(count: u64, total: u64) -> Decimal {
+    if total == 0 {
+        return Decimal::zero()
+    }
+    Decimal::from_ratio(count, total)
+}
+
+pub fn query_trait_frequency(
+    deps: Deps,
+    trait_type: String,
+    value: String,
+) -> StdResult<TraitFrequencyResponse> {
+    let total = _live_tokens(deps.storage).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let count = TRAIT_COUNTS.may_load(deps.storage, (&trait_type, &value))?.unwrap_or_default();
+
+    Ok(TraitFrequencyResponse {
+        trait_type,
+        value,
+        count,
+        total,
+        frequency: _frequency(count, total),
+    })
+}
+
+pub fn query_trait_distribution(
+    deps: Deps,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<TraitDistributionResponse> {
+    let total = _live_tokens(deps.storage).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.as_ref().map(|(t, v)| Bound::exclusive((t.as_str(), v.as_str())));
+
+    let traits = TRAIT_COUNTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|((trait_type, value), count)| TraitFrequencyResponse {
+            trait_type,
+            value,
+            count,
+            total,
+            frequency: _frequency(count, total),
+        }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TraitDistributionResponse { traits })
+}
+
+// the score of a token is the sum of 1 / frequency of each of its traits, rarer traits weight more
+pub fn query_rarity(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<RarityResponse> {
+    let cw721_contract = CW721Contract::default();
+    let token = cw721_contract.tokens.load(deps.storage, &token_id)?;
+    let total = _live_tokens(deps.storage).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let mut score = Decimal::zero();
+    let mut traits: Vec<TraitFrequencyResponse> = vec![];
+
+    for attr in _token_traits(&token.extension) {
+        let count = TRAIT_COUNTS.may_load(deps.storage, (&attr.trait_type, &attr.value))?.unwrap_or_default();
+        if count > 0 {
+            score += Decimal::from_ratio(total, count);
+        }
+
+        traits.push(TraitFrequencyResponse {
+            trait_type: attr.trait_type,
+            value: attr.value,
+            count,
+            total,
+            frequency: _frequency(count, total),
+        });
+    }
+
+    Ok(RarityResponse { token_id, score, traits })
+}
+
+// paginated by token id, straight from the (trait_type, value) index
+pub fn query_tokens_by_trait(
+    deps: Deps,
+    trait_type: String,
+    value: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let tokens = TRAIT_TOKENS
+        .prefix((&trait_type, &value))
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
+// walks the tokens of the owner (cw721 owner index) and keeps the ones found in the trait index.
+// at most MAX_SCAN_LIMIT tokens of the owner are read per call, 'next' is where the following call
+// continues (start_after), None once every token of the owner was read
+pub fn query_owner_tokens_by_trait(
+    deps: Deps,
+    owner: String,
+    trait_type: String,
+    value: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OwnerTokensByTraitResponse> {
+    let cw721_contract = CW721Contract::default();
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let mut tokens: Vec<String> = vec![];
+    let mut last: Option<String> = None;
+    let mut scanned = 0u32;
+
+    for token_id in cw721_contract.tokens
+        .idx
+        .owner
+        .prefix(owner)
+        .keys(deps.storage, start, None, Order::Ascending)
+    {
+        if tokens.len() == limit || scanned == MAX_SCAN_LIMIT {
+            return Ok(OwnerTokensByTraitResponse { tokens, next: last })
+        }
+
+        let token_id = token_id?;
+        scanned += 1;
+
+        if TRAIT_TOKENS.has(deps.storage, (&trait_type, &value, &token_id)) {
+            tokens.push(token_id.clone());
+        }
+        last = Some(token_id);
+    }
+
+    Ok(OwnerTokensByTraitResponse { tokens, next: None })
+}
+
+// the sha256 of the uri and metadata a stored token has now, None once the token is retired
+pub fn query_provenance(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<ProvenanceResponse> {
+    let hash = PROVENANCE.may_load(deps.storage, &token_id)?;
+
+    Ok(ProvenanceResponse { token_id, hash })
+}
+
+pub fn query_freeze(
+    deps: Deps,
+) -> StdResult<FreezeResponse> {
+    let freeze = METADATA_FREEZE.may_load(deps.storage)?;
+
+    Ok(FreezeResponse {
+        frozen: freeze.as_ref().map_or(false, |f| f.all.is_some()),
+        frozen_at: freeze.as_ref().and_then(|f| f.all),
+        ranges: freeze.map(|f| f.ranges).unwrap_or_default(),
+    })
+}
+
+// oldest change first, paginated by the history seq
+pub fn query_trait_history(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TraitHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let history = TRAIT_HISTORY
+        .prefix(&token_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<Vec<TraitChange>>>()?;
+
+    Ok(TraitHistoryResponse { token_id, history })
+}
+
+// the tokens burnt to make token_id, and the token it was evolved into once it is burnt for an evolution.
+// the lineage of a burnt token is kept, so the whole tree can be walked
+pub fn query_lineage(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<LineageResponse> {
+    let lineage = LINEAGE.may_load(deps.storage, &token_id)?;
+    let child = EVOLVED_INTO.may_load(deps.storage, &token_id)?;
+
+    Ok(LineageResponse {
+        token_id,
+        parents: lineage.as_ref().map(|l| l.parents.clone()).unwrap_or_default(),
+        owner: lineage.as_ref().map(|l| l.owner.to_string()),
+        height: lineage.map(|l| l.height),
+        child,
+    })
+}
+
+pub fn query_edition(
+    deps: Deps,
+    template_id: String,
+) -> StdResult<EditionResponse> {
+    let edition = EDITIONS.load(deps.storage, &template_id)?;
+
+    Ok(EditionResponse {
+        template_id,
+        remaining: edition.cap.map(|cap| cap - edition.minted),
+        minted: edition.minted,
+        cap: edition.cap,
+        start: edition.start,
+        end: edition.end,
+        token_uri: edition.token_uri,
+        metadata: edition.metadata,
+    })
+}
+
+fn _series_response(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    series_id: String,
+    series: Series,
+) -> StdResult<SeriesResponse> {
+    let remaining = _series_available_count(deps.storage, &series_id)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(SeriesResponse {
+        live: _is_mint_live(&_series_config(config, &series), &env.block.time),
+        series_id,
+        name: series.store_conf.name.clone(),
+        token_supply: series.token_supply,
+        stored: series.stored,
+        minted: series.minted,
+        remaining,
+        cost_amount: series.cost_amount,
+        cost_denom: series.cost_denom,
+        gated_mint: series.gated_mint,
+        start_mint: series.start_mint,
+        end_mint: series.end_mint,
+        revealed: series.revealed,
+        store_conf: series.store_conf,
+    })
+}
+
+pub fn query_series(
+    deps: Deps,
+    env: Env,
+    series_id: String,
+) -> StdResult<SeriesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let series = SERIES.load(deps.storage, &series_id)?;
+
+    _series_response(deps, &env, &config, series_id, series)
+}
+
+// paginated by series id
+pub fn query_all_series(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllSeriesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let series = SERIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(series_id, series)| _series_response(deps, &env, &config, series_id, series)))
+        .collect::<StdResult<Vec<SeriesResponse>>>()?;
+
+    Ok(AllSeriesResponse { series })
+}
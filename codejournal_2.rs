@@ -2,25 +2,42 @@
 // THIS FILE CONTAINS A SET OF UTILITY FUNCTIONS, THAT BECAUSE OF THEIR USAGE IN MULTIPLE FILES,
 // ARE CONDENSED HERE, WITH THE PORPUSE OF REUSIBILITY
 
+use std::collections::HashSet;
 use std::time::{
     SystemTime,   
     UNIX_EPOCH
   };
   
+  use sha2::{ Digest, Sha256 };
+
   use cosmwasm_std::{
+    to_binary,
+    Deps,
     DepsMut,
+    Decimal,
+    Order,
     MessageInfo,
     Coin,
     Uint128,
     Storage,
+    StdResult,
     Addr,
-    Timestamp
+    Binary,
+    Empty,
+    Env,
+    Timestamp,
+    Event,
+    BankMsg,
+    CosmosMsg
   };
   
+  use cw721::{ Cw721QueryMsg, OwnerOfResponse };
   use cw721_base::{ MintMsg };
   use cw721_base::state::{ TokenInfo };
+  use cw_storage_plus::Bound;
   
   // use crate::msg::StoreConf;
+  use crate::msg::{ PartnerToken, SignedVoucher, LazyMintMsg, LazyMintPayload, StoreConf, TraitSchema, UpgradePayload };
   
   use crate::state::{
     CW721Contract,
@@ -29,10 +46,70 @@ use std::time::{
     Config,
     BURNT_AMOUNT,
     BURNT_LIST,
-    BURNED
+    BURNED,
+    ROLES,
+    Role,
+    MINTER_RENOUNCED,
+    HANDOVER,
+    PAUSED,
+    PauseTarget,
+    AVAILABLE,
+    AVAILABLE_IDX,
+    AVAILABLE_COUNT,
+    STORE_SEQ,
+    CONF_SEQ,
+    RESERVE_USED,
+    MINTED,
+    Partner,
+    PARTNER_USES,
+    VOUCHER_NONCES,
+    SUPPLY,
+    SupplyLedger,
+    Trait,
+    TRAIT_COUNTS,
+    TRAIT_TOKENS,
+    METADATA_FREEZE,
+    FrozenRange,
+    TRAIT_HISTORY,
+    UPGRADE_CONSENT,
+    EvolveRules,
+    EvolveOp,
+    Edition,
+    EDITIONS,
+    Series,
+    SERIES,
+    SERIES_AVAILABLE,
+    SERIES_AVAILABLE_COUNT,
+    TOKEN_SERIES,
+    PROVENANCE,
+    STORE_CONFS,
+    TOKEN_CONF,
+    CONF_VERSION,
+    Metadata
   };
   
   use crate::error::ContractError;
+
+  // EVENT SCHEMA. EVERY MINTED, BURNT OR STORED TOKEN EMITS ITS OWN EVENT, ON TOP OF THE SUMMARY ATTRIBUTES OF THE RESPONSE.
+  // the chain prefixes the type with 'wasm-', so indexers see 'wasm-mint', 'wasm-burn' and 'wasm-store'
+  pub const EVENT_MINT: &str = "mint";
+  pub const EVENT_BURN: &str = "burn";
+  pub const EVENT_STORE: &str = "store";
+
+  pub const ATTR_TOKEN_ID: &str = "token_id";
+  pub const ATTR_OWNER: &str = "owner";
+  pub const ATTR_PRICE: &str = "price";   // amount and denom paid for the token, ex: '1000000ujuno'
+  pub const ATTR_PHASE: &str = "phase";   // one of the PHASE_* values below
+
+  pub const PHASE_GATED: &str = "gated";
+  pub const PHASE_PUBLIC: &str = "public";
+  pub const PHASE_AIRDROP: &str = "airdrop";
+  pub const PHASE_LAZY: &str = "lazy";
+  pub const PHASE_EVOLVE: &str = "evolve";
+  pub const PHASE_EDITION: &str = "edition";
+
+  pub const EDITION_TRAIT: &str = "edition";
+  pub const EVOLVED_TOKEN_PREFIX: &str = "evolved-";
   
   
   // USING THE 'Timestamp' STRUCT AND THE RESPECTIVE ASSOCIATED FUNCTION 'from_seconds' FROM THE 'cosmwasm_std' CRATE, 
@@ -50,6 +127,11 @@ use std::time::{
     info: &MessageInfo // 
   ) -> Result<(), ContractError> {
     let cw721_contract = CW721Contract::default();
+
+    // once the minter renounced, nobody can update the collection anymore
+    if MINTER_RENOUNCED.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::Renounced {});
+    }
   
     let minter = cw721_contract.minter.load(deps.storage)?; // Load minter address from storage
   
@@ -72,6 +154,115 @@ use std::time::{
   }
   
 
+  // FUNCTION THAT EVALUATES IF THE CALLER OF THE CONTRACT HOLDS THE GIVEN ROLE.
+  // THE MINTER (ADMIN) IS ALLOWED TO PERFORM EVERY ROLE, SO A COLLECTION WITHOUT GRANTED ROLES BEHAVES AS BEFORE
+  pub fn can_role(
+    deps: &DepsMut,
+    info: &MessageInfo,
+    role: Role
+  ) -> Result<(), ContractError> {
+    match can_update(deps, info) {
+      Ok(()) => return Ok(()),
+      Err(ContractError::Renounced {}) => return Err(ContractError::Renounced {}),
+      Err(_) => {}
+    }
+
+    if !ROLES.has(deps.storage, (role.as_str(), &info.sender)) {
+      return Err(ContractError::MissingRole { role: role.to_string() });
+    }
+
+    Ok(())
+  }
+
+  // fails when the given action is paused. a pause with an 'until' time lifts by itself once the time is reached
+  pub fn not_paused(
+    storage: &dyn Storage,
+    time: &Timestamp,
+    target: PauseTarget
+  ) -> Result<(), ContractError> {
+    if let Some(pause) = PAUSED.may_load(storage, target.as_str())? {
+      let expired = match pause.until {
+        Some(until) => *time >= until,
+        None => false,
+      };
+
+      if !expired {
+        return Err(ContractError::Paused {
+          target: target.to_string(),
+          reason: pause.reason.unwrap_or_default()
+        });
+      }
+    }
+
+    Ok(())
+  }
+
+  // stored tokens of the previous minter are handed over in pages after a minter transfer.
+  // until the last page is done, the stored tokens can't be minted or swept
+  pub fn not_handing_over(
+    storage: &dyn Storage
+  ) -> Result<(), ContractError> {
+    if let Some(from) = HANDOVER.may_load(storage)? {
+      return Err(ContractError::HandoverPending { from: from.to_string() });
+    }
+
+    Ok(())
+  }
+
+  // the stored tokens still owned by the previous minter belong to the collection, the previous minter
+  // (or an operator it approved) can't transfer them away while they wait for the handover
+  pub fn can_transfer(
+    storage: &dyn Storage,
+    contract: &CW721Contract,
+    token_id: &str
+  ) -> Result<(), ContractError> {
+    let token = contract.tokens.load(storage, token_id)?;
+    if HANDOVER.may_load(storage)?.map_or(false, |from| from == token.owner) {
+      not_handing_over(storage)?;
+    }
+
+    Ok(())
+  }
+
+  // moves up to 'limit' stored tokens from the previous minter to the current one, returns how many moved
+  // and ends the handover once the previous minter owns nothing anymore
+  pub fn handover_stored(
+    storage: &mut dyn Storage,
+    contract: &CW721Contract,
+    limit: usize
+  ) -> Result<(usize, bool), ContractError> {
+    let from = match HANDOVER.may_load(storage)? {
+      Some(from) => from,
+      None => return Ok((0, true)),
+    };
+    let to = contract.minter.load(storage)?;
+
+    let page: Vec<String> = contract.tokens
+      .idx
+      .owner
+      .prefix(from)
+      .keys(storage, None, None, Order::Ascending)
+      .take(limit + 1)
+      .collect::<StdResult<_>>()?;
+
+    let done = page.len() <= limit;
+    let moved = page.len().min(limit);
+
+    for token_id in page.iter().take(moved) {
+      contract.tokens.update(storage, token_id, |token| -> Result<_, ContractError> {
+        let mut token = token.ok_or(ContractError::NotFound {})?;
+        token.owner = to.clone();
+        Ok(token)
+      })?;
+    }
+
+    if done {
+      HANDOVER.remove(storage);
+    }
+
+    Ok((moved, done))
+  }
+
   pub fn update_burnt_amount(
     storage: &mut dyn Storage, // mutable storage
     sender: &Addr,
@@ -109,23 +300,145 @@ use std::time::{
     }
   }
   
+  // returns the owner of the burnt token
   pub fn burn_token(
     contract: &CW721Contract,
     storage: &mut dyn Storage,
     token_id: String
-  ) -> Result<(), ContractError> {
+  ) -> Result<Addr, ContractError> {
+    // a token still owned by the minter was never minted, it is not part of token_count
+    let token = contract.tokens.load(storage, &token_id)?;
+    if token.owner == contract.minter.load(storage)? {
+      return retire_token(contract, storage, token_id);
+    }
+
+    // a stored token still waiting for the handover is not a minted one either
+    if HANDOVER.may_load(storage)?.map_or(false, |from| from == token.owner) {
+      not_handing_over(storage)?;
+    }
+
     contract.tokens.remove(storage, &token_id)?; // the tokens map is a propertie of the cw721 base contract, and it holds the information about existing stored tokens
+    unindex_traits(storage, &token_id, &token.extension)?;
+    TOKEN_CONF.remove(storage, &token_id);
     contract.decrement_tokens(storage)?; //  the cw721 base contract have a propertie called token_count that tracks the amout of tokens stored. The decrement_tokens() is a helper function to reduce by one the existing number
-    BURNED.save(storage, token_id, &true)?; // save the the token_id in the map 
-    Ok(())
+    BURNED.save(storage, token_id.clone(), &true)?; // save the the token_id in the map 
+    remove_available(storage, &token_id)?; // a burnt token can't be minted anymore
+    update_supply(storage, |ledger| ledger.burnt_after_mint += 1)?;
+    Ok(token.owner)
   }
   
-  // This function makes sure that only the address set as the minter is allowed to store, and that # of current stored NFTs is less or equal the # defined as max supply
+  // checks that the batch size is inside the range set in the config (min_burn_batch..=max_burn_batch)
+  pub fn can_burn_batch_size(
+    config: &Config,
+    size: usize
+  ) -> Result<(), ContractError> {
+    let requested = Uint128::from(size as u128);
+
+    if requested < config.min_burn_batch {
+      return Err(ContractError::RequestTooSmall { size });
+    }
+
+    if requested > config.max_burn_batch {
+      return Err(ContractError::RequestTooLarge { size });
+    }
+
+    Ok(())
+  }
+
+  // validates the whole batch before any token is burnt, so the burn is all or none.
+  // every offending token is collected, instead of returning on the first one.
+  // when an owner is given, every token must belong to it (owner burn), otherwise only existence is checked (minter burn)
+  pub fn can_burn_batch(
+    storage: &dyn Storage,
+    contract: &CW721Contract,
+    tokens: &[String],
+    owner: Option<&Addr>
+  ) -> Result<(), ContractError> {
+    let mut seen: HashSet<&String> = HashSet::new();
+    let mut invalid: Vec<String> = vec![];
+
+    for token_id in tokens {
+      if !seen.insert(token_id) {
+        invalid.push(format!("{}: duplicated", token_id));
+        continue;
+      }
+
+      match contract.tokens.may_load(storage, token_id)? {
+        None => invalid.push(format!("{}: not found", token_id)),
+        Some(token) => {
+          if let Some(owner) = owner {
+            if &token.owner != owner {
+              invalid.push(format!("{}: not owned by sender", token_id));
+            }
+          }
+        }
+      }
+    }
+
+    if !invalid.is_empty() {
+      return Err(ContractError::InvalidBurnBatch { tokens: invalid });
+    }
+
+    Ok(())
+  }
+
+  // removes a stored token that was never minted. unlike burn_token, the cw721 token_count is not decremented,
+  // because the count is only incremented when a stored token is minted (see try_mint). token_total goes down instead
+  pub fn retire_token(
+    contract: &CW721Contract,
+    storage: &mut dyn Storage,
+    token_id: String
+  ) -> Result<Addr, ContractError> {
+    let token = contract.tokens.load(storage, &token_id)?;
+    contract.tokens.remove(storage, &token_id)?;
+    unindex_traits(storage, &token_id, &token.extension)?;
+    remove_available(storage, &token_id)?;
+    PROVENANCE.remove(storage, &token_id);
+    TOKEN_CONF.remove(storage, &token_id);
+
+    // a series token also leaves the total of its series
+    if let Some(series_id) = TOKEN_SERIES.may_load(storage, &token_id)? {
+      SERIES.update(storage, &series_id, |series| -> Result<_, ContractError> {
+        let mut series = series.ok_or(ContractError::NotFound {})?;
+        series.stored = series.stored.checked_sub(Uint128::from(1u8))?;
+        Ok(series)
+      })?;
+    }
+
+    BURNED.save(storage, token_id, &true)?;
+
+    let mut config = CONFIG.load(storage)?;
+    config.token_total = config.token_total.checked_sub(Uint128::from(1u8))?;
+    CONFIG.save(storage, &config)?;
+
+    update_supply(storage, |ledger| ledger.burnt_before_mint += 1)?;
+    Ok(token.owner)
+  }
+
+  // minting is live from start_mint (or right away when not set) until end_mint (or forever when not set)
+  pub fn is_mint_live(
+    config: &Config,
+    time: &Timestamp
+  ) -> bool {
+    let started = match config.start_mint {
+      Some(stamp) => *time >= stamp,
+      None => true,
+    };
+
+    let ended = match config.end_mint {
+      Some(stamp) => *time >= stamp,
+      None => false,
+    };
+
+    started && !ended
+  }
+
+  // This function makes sure that only the minter or a store-admin is allowed to store, and that # of current stored NFTs is less or equal the # defined as max supply
   pub fn can_store(
     deps: &DepsMut,
     info: &MessageInfo
   ) -> Result<(), ContractError> {
-    can_update(deps, info)?;  // makes sure that only the address set as the minter is allowed to store
+    can_role(deps, info, Role::StoreAdmin)?;  // makes sure that only the minter or a store-admin is allowed to store
   
     let config = CONFIG.load(deps.storage)?; // loads the contract config
     if config.token_total >= config.token_supply {
@@ -139,16 +452,33 @@ use std::time::{
     config: &Config,
     info: &MessageInfo,
     amount: Uint128
+  ) -> Result<Coin, ContractError> {
+    can_pay_at(config, info, amount, config.cost_amount)
+  }
+
+  // same as can_pay, but with the unit price given by the caller (ex: the partner discount)
+  pub fn can_pay_at(
+    config: &Config,
+    info: &MessageInfo,
+    amount: Uint128,
+    price: Uint128
   ) -> Result<Coin, ContractError> {
     let mut coin_found: Coin = Coin::new(0, "none");
+
+    // free mints (ex: a voucher with price 0) take no funds at all
+    if (price * amount).is_zero() {
+      if info.funds.iter().any(|coin| !coin.amount.is_zero()) {
+        return Err(ContractError::IncorrectFunds {});
+      }
+      return Ok(Coin::new(0, config.cost_denom.clone()));
+    }
   
     if let Some(coin) = info.funds.first() { // DOCS -> The funds that are sent to the contract as part of `MsgInstantiateContract` or `MsgExecuteContract`. The transfer is processed in bank before the contract
                                                 //  is executed such that the new balance is visible during contract execution.
         if coin.denom != config.cost_denom {
           Err(ContractError::WrongToken {})
         } else {
-            let total = config.cost_amount * amount;
-            println!("NotEnoughFunds: {} {} {}", coin.amount < total, coin.amount, total);
+            let total = price * amount;
             if coin.amount < total {
                 return Err(ContractError::NotEnoughFunds {});
             }
@@ -167,10 +497,26 @@ use std::time::{
     }
   }
   
+  // the transfer of the mint payment to the funds wallet, nothing to send for a free mint
+  pub fn payment_msgs(
+    config: &Config,
+    paid: &Coin
+  ) -> Vec<CosmosMsg> {
+    if paid.amount.is_zero() {
+      return vec![];
+    }
+
+    vec![CosmosMsg::Bank(BankMsg::Send {
+      to_address: config.funds_wallet.to_string(),
+      amount: vec![paid.clone()],
+    })]
+  }
+
   pub fn can_mint(
     count: &u64,
     time: &Timestamp,
     start_mint: &Option<Timestamp>,
+    end_mint: &Option<Timestamp>,
     token_total: Uint128,
     token_supply: Uint128,
     minter: &Addr,
@@ -185,6 +531,13 @@ use std::time::{
         return Err(ContractError::CantMintYet {})
       }
     }
+
+    // the sale is over once end_mint is reached, whatever supply is left
+    if let Some(stamp) = end_mint {
+      if time >= stamp {
+        return Err(ContractError::MintEnded {})
+      }
+    }
   
     let current_count = Uint128::from(*count);
   
@@ -204,6 +557,7 @@ use std::time::{
     Ok(current_count)
   }
   
+  // adds 'amount' newly stored tokens on top of the current total, callers pass the number stored, not the new total
   pub fn update_total(
     storage: &mut dyn Storage,
     amount: Uint128
@@ -211,9 +565,22 @@ use std::time::{
     let mut config = CONFIG.load(storage)?;
     config.token_total += amount;
     CONFIG.save(storage, &config)?;
+    update_supply(storage, |ledger| ledger.stored += amount.u128() as u64)?;
     Ok(())
   }
   
+  // the id store_conf renders next. it only grows, token_total can't be used because retired tokens lower it
+  // and the next rendered id would be one already stored. collections without the counter start after every
+  // token ever stored, which is past any id rendered from token_total before
+  pub fn next_conf_id(
+    storage: &dyn Storage
+  ) -> Result<Uint128, ContractError> {
+    match CONF_SEQ.may_load(storage)? {
+      Some(seq) => Ok(seq),
+      None => Ok(Uint128::from(SUPPLY.may_load(storage)?.unwrap_or_default().stored)),
+    }
+  }
+
   pub fn try_store(
     storage: &mut dyn Storage,
     nft_data: &MintMsg<Extension>,
@@ -221,6 +588,14 @@ use std::time::{
     contract: &CW721Contract,
   ) -> Result<(), ContractError> {
     let token_id = nft_data.token_id.clone();
+
+    can_edit_metadata(storage, &token_id)?;
+    not_edition_id(storage, &token_id)?;
+
+    // storing over an existing token would replace it (and its owner) without the supply knowing
+    if contract.tokens.has(storage, &token_id) {
+      return Err(ContractError::Claimed {})
+    }
   
     // create the token
     let token = TokenInfo {
@@ -231,6 +606,9 @@ use std::time::{
     };
   
     contract.tokens.save(storage, &token_id, &token)?;
+    push_available(storage, &token_id)?;
+    index_traits(storage, &token_id, &token.extension)?;
+    record_provenance(storage, &token_id, &token.token_uri, &token.extension)?;
   
     Ok(())
   }
@@ -242,6 +620,8 @@ use std::time::{
     contract: &CW721Contract,
     current: &String
   ) -> Result<(), ContractError> {
+    not_handing_over(storage)?;
+
     let old_token = contract.tokens.load(storage, current)?;
     if old_token.owner != minter.clone() {
       return Err(ContractError::Claimed {})
@@ -250,5 +630,1082 @@ use std::time::{
     new_token.owner = sender.clone();
     contract.tokens.replace(storage, current, Some(&new_token), Some(&old_token))?;
     contract.increment_tokens(storage)?;
+    remove_available(storage, current)?;
+    update_supply(storage, |ledger| ledger.minted += 1)?;
+    Ok(())
+  }
+
+  // THE AVAILABLE INDEX KEEPS THE STORED TOKENS THAT CAN STILL BE MINTED, IN THE ORDER THEY WERE STORED.
+  // AVAILABLE maps the store sequence to the token id, AVAILABLE_IDX is the reverse lookup used to remove a given id
+  pub fn push_available(
+    storage: &mut dyn Storage,
+    token_id: &str
+  ) -> Result<(), ContractError> {
+    // storing the same id again keeps its place in the queue
+    if AVAILABLE_IDX.has(storage, token_id) {
+      return Ok(());
+    }
+
+    let seq = STORE_SEQ.may_load(storage)?.unwrap_or_default();
+    AVAILABLE.save(storage, seq, &token_id.to_string())?;
+    AVAILABLE_IDX.save(storage, token_id, &seq)?;
+    STORE_SEQ.save(storage, &(seq + 1))?;
+
+    let count = AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default();
+    AVAILABLE_COUNT.save(storage, &(count + 1))?;
+
+    Ok(())
+  }
+
+  // returns true when the token was in the index
+  pub fn remove_available(
+    storage: &mut dyn Storage,
+    token_id: &str
+  ) -> Result<bool, ContractError> {
+    match AVAILABLE_IDX.may_load(storage, token_id)? {
+      Some(seq) => {
+        // a series token is only in the queue of its series, and only counted there
+        match TOKEN_SERIES.may_load(storage, token_id)? {
+          Some(series_id) => {
+            SERIES_AVAILABLE.remove(storage, (&series_id, seq));
+            let count = SERIES_AVAILABLE_COUNT.may_load(storage, &series_id)?.unwrap_or_default();
+            SERIES_AVAILABLE_COUNT.save(storage, &series_id, &count.saturating_sub(1))?;
+          },
+          None => {
+            AVAILABLE.remove(storage, seq);
+            let count = AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default();
+            AVAILABLE_COUNT.save(storage, &count.saturating_sub(1))?;
+          },
+        }
+        AVAILABLE_IDX.remove(storage, token_id);
+
+        Ok(true)
+      },
+      None => Ok(false)
+    }
+  }
+
+  // the next 'limit' tokens to be minted, in order
+  pub fn peek_available(
+    storage: &dyn Storage,
+    limit: usize
+  ) -> Result<Vec<String>, ContractError> {
+    let ids = AVAILABLE
+      .range(storage, None, None, Order::Ascending)
+      .take(limit)
+      .map(|item| item.map(|(_, token_id)| token_id))
+      .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(ids)
+  }
+
+  // the next token to be minted, the oldest one stored
+  pub fn next_available(
+    storage: &dyn Storage
+  ) -> Result<String, ContractError> {
+    match peek_available(storage, 1)?.pop() {
+      Some(token_id) => Ok(token_id),
+      None => Err(ContractError::MaxTokens {})
+    }
+  }
+
+  // a stored token that belongs to a series. it goes to the queue of its series instead of AVAILABLE and is
+  // counted apart, so the collection mints and the reserve never see series tokens. it shares the store
+  // sequence and AVAILABLE_IDX so remove_available finds every stored token
+  pub fn push_series(
+    storage: &mut dyn Storage,
+    series_id: &str,
+    token_id: &str
+  ) -> Result<(), ContractError> {
+    if AVAILABLE_IDX.has(storage, token_id) {
+      return Ok(());
+    }
+
+    let seq = STORE_SEQ.may_load(storage)?.unwrap_or_default();
+    SERIES_AVAILABLE.save(storage, (series_id, seq), &token_id.to_string())?;
+    AVAILABLE_IDX.save(storage, token_id, &seq)?;
+    TOKEN_SERIES.save(storage, token_id, &series_id.to_string())?;
+    STORE_SEQ.save(storage, &(seq + 1))?;
+
+    let count = SERIES_AVAILABLE_COUNT.may_load(storage, series_id)?.unwrap_or_default();
+    SERIES_AVAILABLE_COUNT.save(storage, series_id, &(count + 1))?;
+
+    Ok(())
+  }
+
+  // the next 'limit' tokens of the series to be minted, in order
+  pub fn peek_series_available(
+    storage: &dyn Storage,
+    series_id: &str,
+    limit: usize
+  ) -> Result<Vec<String>, ContractError> {
+    let ids = SERIES_AVAILABLE
+      .prefix(series_id)
+      .range(storage, None, None, Order::Ascending)
+      .take(limit)
+      .map(|item| item.map(|(_, token_id)| token_id))
+      .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(ids)
+  }
+
+  // the next token of the series to be minted, the oldest one stored
+  pub fn next_series_available(
+    storage: &dyn Storage,
+    series_id: &str
+  ) -> Result<String, ContractError> {
+    match SERIES_AVAILABLE.prefix(series_id).range(storage, None, None, Order::Ascending).next() {
+      Some(item) => Ok(item?.1),
+      None => Err(ContractError::MaxTokens {})
+    }
+  }
+
+  pub fn series_available_count(
+    storage: &dyn Storage,
+    series_id: &str
+  ) -> Result<u64, ContractError> {
+    Ok(SERIES_AVAILABLE_COUNT.may_load(storage, series_id)?.unwrap_or_default())
+  }
+
+  // the collection config seen by a mint of the series: its own supply, schedule, price and store_conf
+  pub fn series_config(
+    config: &Config,
+    series: &Series
+  ) -> Config {
+    Config {
+      start_mint: series.start_mint,
+      end_mint: series.end_mint,
+      gated_mint: series.gated_mint,
+      cost_amount: series.cost_amount,
+      cost_denom: series.cost_denom.clone(),
+      token_total: series.stored,
+      token_supply: series.token_supply,
+      store_conf: Some(series.store_conf.clone()),
+      ..config.clone()
+    }
+  }
+
+  pub fn available_count(
+    storage: &dyn Storage
+  ) -> Result<Uint128, ContractError> {
+    Ok(Uint128::from(AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default()))
+  }
+
+  pub fn reserve_remaining(
+    storage: &dyn Storage,
+    config: &Config
+  ) -> Result<Uint128, ContractError> {
+    let used = RESERVE_USED.may_load(storage)?.unwrap_or_default();
+    Ok(config.reserved.saturating_sub(used))
+  }
+
+  // public mints can only take the available tokens that are not held back for the team reserve. the reserve
+  // only holds back collection tokens, a series mint is checked against the queue of its series alone
+  pub fn can_mint_public(
+    storage: &dyn Storage,
+    config: &Config,
+    series_id: Option<&str>,
+    amount: Uint128
+  ) -> Result<(), ContractError> {
+    if let Some(series_id) = series_id {
+      if Uint128::from(series_available_count(storage, series_id)?) < amount {
+        return Err(ContractError::MaxTokens {});
+      }
+      return Ok(());
+    }
+
+    let available = available_count(storage)?;
+    let reserved = reserve_remaining(storage, config)?;
+
+    if available.saturating_sub(reserved) < amount {
+      return Err(ContractError::ReservedSupply { reserved });
+    }
+
+    Ok(())
+  }
+
+  // checks the per wallet limit against the address receiving the tokens, not the one paying for them
+  pub fn can_mint_wallet(
+    storage: &dyn Storage,
+    config: &Config,
+    recipient: &Addr,
+    amount: Uint128
+  ) -> Result<(), ContractError> {
+    if let Some(max) = config.max_per_wallet {
+      let minted = MINTED.may_load(storage, recipient)?.unwrap_or_default();
+      if minted + amount > max {
+        return Err(ContractError::MaxPerWallet { max });
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn update_minted(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    amount: Uint128
+  ) -> Result<(), ContractError> {
+    MINTED.update(storage, recipient, |minted| -> Result<_, ContractError> {
+      Ok(minted.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+  }
+
+  // the unit price for a mint, partner holders get the configured discount
+  pub fn mint_price(
+    config: &Config,
+    partner: Option<&Partner>
+  ) -> Uint128 {
+    match partner {
+      Some(partner) => config.cost_amount - config.cost_amount * partner.discount,
+      None => config.cost_amount,
+    }
+  }
+
+  // verifies, with an OwnerOf query to the partner cw721 contract, that the recipient holds the presented token
+  // and that the token still has uses left for the amount being minted
+  pub fn can_use_partner(
+    deps: Deps,
+    config: &Config,
+    recipient: &Addr,
+    proof: &PartnerToken,
+    amount: u32
+  ) -> Result<Partner, ContractError> {
+    let contract = deps.api.addr_validate(&proof.contract)?;
+
+    let partner = config.partners
+      .iter()
+      .find(|p| p.address == contract)
+      .cloned()
+      .ok_or(ContractError::UnknownPartner { contract: contract.to_string() })?;
+
+    let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+      contract.to_string(),
+      &Cw721QueryMsg::OwnerOf { token_id: proof.token_id.clone(), include_expired: None }
+    )?;
+
+    if owner.owner != recipient.as_str() {
+      return Err(ContractError::NotPartnerHolder { contract: contract.to_string(), token_id: proof.token_id.clone() });
+    }
+
+    let used = PARTNER_USES.may_load(deps.storage, (&contract, &proof.token_id))?.unwrap_or_default();
+    if used + amount > partner.max_uses {
+      return Err(ContractError::PartnerTokenUsed { contract: contract.to_string(), token_id: proof.token_id.clone() });
+    }
+
+    Ok(partner)
+  }
+
+  pub fn use_partner(
+    storage: &mut dyn Storage,
+    contract: &Addr,
+    token_id: &str,
+    amount: u32
+  ) -> Result<(), ContractError> {
+    PARTNER_USES.update(storage, (contract, token_id), |used| -> Result<_, ContractError> {
+      Ok(used.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+  }
+
+  // checks a mint voucher signed off-chain by the key set in config.voucher_signer.
+  // the contract address is part of the signed message, so a voucher can't be replayed on another collection
+  pub fn can_use_voucher(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    recipient: &Addr,
+    signed: &SignedVoucher,
+    amount: u32
+  ) -> Result<(), ContractError> {
+    let signer = config.voucher_signer.as_ref().ok_or(ContractError::VouchersDisabled {})?;
+    let voucher = &signed.voucher;
+
+    if voucher.recipient != recipient.as_str() {
+      return Err(ContractError::InvalidVoucher { reason: "recipient".to_string() });
+    }
+
+    if env.block.time >= voucher.expires {
+      return Err(ContractError::InvalidVoucher { reason: "expired".to_string() });
+    }
+
+    if amount > voucher.max_quantity {
+      return Err(ContractError::InvalidVoucher { reason: "quantity".to_string() });
+    }
+
+    if VOUCHER_NONCES.has(deps.storage, voucher.nonce) {
+      return Err(ContractError::InvalidVoucher { reason: "nonce used".to_string() });
+    }
+
+    if !verify_signature(deps, env, signer, &to_binary(voucher)?, &signed.signature) {
+      return Err(ContractError::InvalidVoucher { reason: "signature".to_string() });
+    }
+
     Ok(())
-  }
\ No newline at end of file
+  }
+
+  // a compressed (33 bytes) or uncompressed (65 bytes) secp256k1 public key, the only ones verify_signature takes
+  pub fn validate_pubkey(
+    pubkey: &Binary
+  ) -> Result<(), ContractError> {
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+      return Err(ContractError::InvalidPubkey {});
+    }
+
+    Ok(())
+  }
+
+  // secp256k1 check of sha256(contract address + payload), a failing verification counts as an invalid signature
+  pub fn verify_signature(
+    deps: Deps,
+    env: &Env,
+    signer: &Binary,
+    payload: &Binary,
+    signature: &Binary
+  ) -> bool {
+    let mut message = env.contract.address.as_bytes().to_vec();
+    message.extend_from_slice(payload.as_slice());
+    let hash = Sha256::digest(&message);
+
+    deps.api
+      .secp256k1_verify(&hash, signature, signer)
+      .unwrap_or(false)
+  }
+
+  // a voucher can be used only once, whatever quantity was minted with it
+  pub fn use_voucher(
+    storage: &mut dyn Storage,
+    nonce: u64
+  ) -> Result<(), ContractError> {
+    VOUCHER_NONCES.save(storage, nonce, &true)?;
+    Ok(())
+  }
+
+  // checks a MintMsg signed off-chain by the creator key (config.lazy_signer).
+  // the token id can only be minted once and the signature stops being valid at 'expires'
+  pub fn can_lazy_mint(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    contract: &CW721Contract,
+    msg: &LazyMintMsg
+  ) -> Result<(), ContractError> {
+    let signer = config.lazy_signer.as_ref().ok_or(ContractError::LazyMintDisabled {})?;
+
+    if env.block.time >= msg.expires {
+      return Err(ContractError::Expired {});
+    }
+
+    let token_id = &msg.nft.token_id;
+    if contract.tokens.has(deps.storage, token_id) || BURNED.has(deps.storage, token_id.clone()) {
+      return Err(ContractError::Claimed {});
+    }
+
+    let payload = to_binary(&LazyMintPayload {
+      nft: msg.nft.clone(),
+      expires: msg.expires,
+    })?;
+
+    if !verify_signature(deps, env, signer, &payload, &msg.signature) {
+      return Err(ContractError::InvalidSignature {});
+    }
+
+    Ok(())
+  }
+
+  // THE SUPPLY LEDGER IS THE ONLY PLACE WHERE THE SUPPLY OF THE COLLECTION IS COUNTED.
+  // stored and minted only grow, the remaining tokens are stored - minted - burnt_before_mint
+  pub fn update_supply<F>(
+    storage: &mut dyn Storage,
+    action: F
+  ) -> Result<SupplyLedger, ContractError>
+  where
+    F: FnOnce(&mut SupplyLedger)
+  {
+    let mut ledger = SUPPLY.may_load(storage)?.unwrap_or_default();
+    action(&mut ledger);
+    SUPPLY.save(storage, &ledger)?;
+    Ok(ledger)
+  }
+
+  // debug builds only, called at the end of every execute that changes the supply.
+  // the ledger must agree with the config, the cw721 token_count and the available index
+  pub fn assert_supply(
+    storage: &dyn Storage
+  ) -> Result<(), ContractError> {
+    #[cfg(debug_assertions)]
+    {
+      let ledger = SUPPLY.may_load(storage)?.unwrap_or_default();
+      let config = CONFIG.load(storage)?;
+      let count = CW721Contract::default().token_count(storage)?;
+      // the series tokens are counted apart from the collection ones, the ledger holds both
+      let series_available = SERIES_AVAILABLE_COUNT
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, count)| count))
+        .sum::<StdResult<u64>>()?;
+      let available = AVAILABLE_COUNT.may_load(storage)?.unwrap_or_default() + series_available;
+
+      // editions and evolved tokens are minted without being stored, they are part of token_count but not of token_total
+      let minted = ledger.minted + ledger.editions + ledger.evolved;
+      debug_assert!(minted >= ledger.burnt_after_mint, "burnt more minted tokens than minted");
+      debug_assert!(ledger.stored >= ledger.minted + ledger.burnt_before_mint, "minted or burnt more than stored");
+      debug_assert_eq!(minted - ledger.burnt_after_mint, count, "ledger and token_count disagree");
+      debug_assert_eq!(ledger.stored - ledger.minted - ledger.burnt_before_mint, available, "ledger and available index disagree");
+      debug_assert_eq!(Uint128::from(ledger.stored - ledger.burnt_before_mint), config.token_total, "ledger and token_total disagree");
+      debug_assert!(config.token_total <= config.token_supply, "token_total above token_supply");
+    }
+
+    #[cfg(not(debug_assertions))]
+    let _ = storage;
+
+    Ok(())
+  }
+
+  // the sale phase a mint happens in, before start_mint only the gated phase can be open
+  pub fn mint_phase(
+    config: &Config,
+    time: &Timestamp
+  ) -> &'static str {
+    match config.start_mint {
+      Some(start) if *time < start => PHASE_GATED,
+      _ => PHASE_PUBLIC,
+    }
+  }
+
+  pub fn mint_event(
+    token_id: &str,
+    owner: &Addr,
+    price: &Coin,
+    phase: &str
+  ) -> Event {
+    Event::new(EVENT_MINT)
+      .add_attribute(ATTR_TOKEN_ID, token_id)
+      .add_attribute(ATTR_OWNER, owner)
+      .add_attribute(ATTR_PRICE, price.to_string())
+      .add_attribute(ATTR_PHASE, phase)
+  }
+
+  pub fn burn_event(
+    token_id: &str,
+    owner: &Addr
+  ) -> Event {
+    Event::new(EVENT_BURN)
+      .add_attribute(ATTR_TOKEN_ID, token_id)
+      .add_attribute(ATTR_OWNER, owner)
+  }
+
+  pub fn store_event(
+    token_id: &str,
+    owner: &Addr
+  ) -> Event {
+    Event::new(EVENT_STORE)
+      .add_attribute(ATTR_TOKEN_ID, token_id)
+      .add_attribute(ATTR_OWNER, owner)
+  }
+
+  // TEMPLATES USED BY execute_store_conf TO BUILD EVERY TOKEN FROM THE STORE CONFIGURATION.
+  // placeholders: {base} conf.ipfs, {name} conf.name, {id} the token counter,
+  // {padded_id} the counter with leading zeros up to conf.id_padding digits, {ext} conf.file_ext ('png' when not set)
+  pub const DEFAULT_TOKEN_ID_TEMPLATE: &str = "{id}";
+  pub const DEFAULT_SERIES_TOKEN_ID_TEMPLATE: &str = "{name}-{id}";
+  pub const DEFAULT_NAME_TEMPLATE: &str = "{name} #{id}";
+  pub const DEFAULT_IMAGE_TEMPLATE: &str = "{base}/{id}.{ext}";
+  pub const DEFAULT_FILE_EXT: &str = "png";
+
+  // the uris built from the templates must use one of these schemes
+  pub const URI_PREFIXES: [&str; 3] = ["ipfs://", "ar://", "https://"];
+
+  pub fn render_template(
+    template: &str,
+    conf: &StoreConf,
+    id: Uint128
+  ) -> String {
+    let padding = conf.id_padding.unwrap_or(0) as usize;
+
+    template
+      .replace("{base}", conf.ipfs.trim_end_matches('/'))
+      .replace("{name}", &conf.name)
+      .replace("{padded_id}", &format!("{:0>width$}", id.to_string(), width = padding))
+      .replace("{id}", &id.to_string())
+      .replace("{ext}", conf.file_ext.as_deref().unwrap_or(DEFAULT_FILE_EXT))
+  }
+
+  // renders every template with a sample id, so a broken configuration fails before anything is stored
+  pub fn validate_store_conf(
+    conf: &StoreConf
+  ) -> Result<(), ContractError> {
+    if conf.name.trim().is_empty() || conf.ipfs.trim().is_empty() {
+      return Err(ContractError::InvalidStoreConf { reason: "name and base uri are required".to_string() });
+    }
+
+    let token_id = conf.token_id_template.as_deref().unwrap_or(DEFAULT_TOKEN_ID_TEMPLATE);
+    if !token_id.contains("{id}") && !token_id.contains("{padded_id}") {
+      return Err(ContractError::InvalidStoreConf { reason: "token id template needs {id} or {padded_id}".to_string() });
+    }
+
+    if let Some(schema) = &conf.schema {
+      let mut seen: HashSet<&String> = HashSet::new();
+      for column in schema {
+        if !seen.insert(&column.trait_type) {
+          return Err(ContractError::InvalidStoreConf { reason: format!("{} is declared twice", column.trait_type) });
+        }
+
+        if let (Some(min), Some(max)) = (column.min, column.max) {
+          if min > max {
+            return Err(ContractError::InvalidStoreConf { reason: format!("{} has min above max", column.trait_type) });
+          }
+        }
+      }
+    }
+
+    let uris = [
+      ("image", Some(conf.image_template.as_deref().unwrap_or(DEFAULT_IMAGE_TEMPLATE))),
+      ("animation_url", conf.animation_url_template.as_deref()),
+      ("external_url", conf.external_url_template.as_deref()),
+      ("token_uri", conf.token_uri_template.as_deref()),
+    ];
+
+    for (field, template) in uris {
+      if let Some(template) = template {
+        let uri = render_template(template, conf, Uint128::zero());
+        if !URI_PREFIXES.iter().any(|prefix| uri.starts_with(prefix)) {
+          return Err(ContractError::InvalidStoreConf { reason: format!("{} must start with ipfs://, ar:// or https://", field) });
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  // the columns of a store_conf row. with a schema every column is described by it, without one
+  // the columns are conf.attributes and every value is required, with no other constraint
+  pub fn store_columns(
+    conf: &StoreConf
+  ) -> Vec<TraitSchema> {
+    match &conf.schema {
+      Some(schema) => schema.clone(),
+      None => conf.attributes
+        .iter()
+        .map(|trait_type| TraitSchema {
+          trait_type: trait_type.clone(),
+          required: true,
+          allowed_values: None,
+          min: None,
+          max: None,
+          display_type: None,
+          mutable: false,
+        })
+        .collect(),
+    }
+  }
+
+  // validates one row of values against the columns and builds its traits.
+  // an empty value on an optional column leaves the trait out of the token
+  pub fn build_traits(
+    columns: &[TraitSchema],
+    row: usize,
+    values: &[String]
+  ) -> Result<Vec<Trait>, ContractError> {
+    if values.len() != columns.len() {
+      return Err(ContractError::InvalidRow {
+        row,
+        column: None,
+        reason: format!("expected {} values, got {}", columns.len(), values.len())
+      });
+    }
+
+    let mut traits: Vec<Trait> = vec![];
+
+    for (column, (schema, value)) in columns.iter().zip(values.iter()).enumerate() {
+      let invalid = |reason: String| ContractError::InvalidRow { row, column: Some(column), reason };
+
+      if value.trim().is_empty() {
+        if schema.required {
+          return Err(invalid(format!("{} is required", schema.trait_type)));
+        }
+        continue;
+      }
+
+      check_trait_value(schema, value).map_err(invalid)?;
+
+      traits.push(Trait {
+        display_type: schema.display_type.clone(),
+        trait_type: schema.trait_type.clone(),
+        value: value.clone(),
+      });
+    }
+
+    Ok(traits)
+  }
+
+  // the allowed values and the numeric range of a column, shared by the store rows and the trait upgrades
+  pub fn check_trait_value(
+    schema: &TraitSchema,
+    value: &str
+  ) -> Result<(), String> {
+    if let Some(allowed) = &schema.allowed_values {
+      if !allowed.iter().any(|v| v == value) {
+        return Err(format!("{} is not an allowed value for {}", value, schema.trait_type));
+      }
+    }
+
+    if schema.min.is_some() || schema.max.is_some() {
+      let number: i64 = value
+        .parse()
+        .map_err(|_| format!("{} must be a number", schema.trait_type))?;
+
+      if schema.min.map_or(false, |min| number < min) || schema.max.map_or(false, |max| number > max) {
+        return Err(format!("{} is out of range for {}", value, schema.trait_type));
+      }
+    }
+
+    Ok(())
+  }
+
+  // TRAIT_COUNTS KEEPS HOW MANY LIVE TOKENS (STORED OR MINTED, NOT BURNT) HAVE EACH (trait_type, value),
+  // TRAIT_TOKENS IS THE SECONDARY INDEX (trait_type, value, token_id) USED TO FIND TOKENS BY TRAIT.
+  // both are updated on every store, metadata change and burn, so they always match the tokens on chain.
+  // a token is counted only while it is in TRAIT_TOKENS, so indexing twice (the backfill of older
+  // collections) or unindexing a token that never was indexed leaves the counts right
+  pub fn index_traits(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    extension: &Extension
+  ) -> Result<(), ContractError> {
+    for attr in token_traits(extension) {
+      if TRAIT_TOKENS.has(storage, (&attr.trait_type, &attr.value, token_id)) {
+        continue;
+      }
+      TRAIT_COUNTS.update(storage, (&attr.trait_type, &attr.value), |count| -> Result<_, ContractError> {
+        Ok(count.unwrap_or_default() + 1)
+      })?;
+      TRAIT_TOKENS.save(storage, (&attr.trait_type, &attr.value, token_id), &Empty {})?;
+    }
+    Ok(())
+  }
+
+  pub fn unindex_traits(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    extension: &Extension
+  ) -> Result<(), ContractError> {
+    for attr in token_traits(extension) {
+      if !TRAIT_TOKENS.has(storage, (&attr.trait_type, &attr.value, token_id)) {
+        continue;
+      }
+      let key = (attr.trait_type.as_str(), attr.value.as_str());
+      match TRAIT_COUNTS.may_load(storage, key)?.unwrap_or_default() {
+        0 | 1 => TRAIT_COUNTS.remove(storage, key),
+        count => TRAIT_COUNTS.save(storage, key, &(count - 1))?,
+      }
+      TRAIT_TOKENS.remove(storage, (&attr.trait_type, &attr.value, token_id));
+    }
+    Ok(())
+  }
+
+  pub fn token_traits(
+    extension: &Extension
+  ) -> Vec<Trait> {
+    extension
+      .as_ref()
+      .and_then(|metadata| metadata.attributes.clone())
+      .unwrap_or_default()
+  }
+
+  // the stored tokens minted so far, what can_mint compares to token_total. editions and evolved
+  // tokens are part of the cw721 token_count but were never stored, so they don't use up the total
+  pub fn minted_count(
+    storage: &dyn Storage
+  ) -> Result<u64, ContractError> {
+    Ok(SUPPLY.may_load(storage)?.unwrap_or_default().minted)
+  }
+
+  // live tokens, the base of every frequency
+  pub fn live_tokens(
+    storage: &dyn Storage
+  ) -> Result<u64, ContractError> {
+    let ledger = SUPPLY.may_load(storage)?.unwrap_or_default();
+    Ok(ledger.stored + ledger.editions + ledger.evolved - ledger.burnt_before_mint - ledger.burnt_after_mint)
+  }
+
+  // the number a frozen range compares, the id without the range prefix. ids not of the form
+  // {prefix}{number} are outside the range
+  pub fn range_number(
+    range: &FrozenRange,
+    token_id: &str
+  ) -> Option<u64> {
+    token_id
+      .strip_prefix(range.prefix.as_deref().unwrap_or(""))
+      .and_then(|number| number.parse::<u64>().ok())
+  }
+
+  // fails once the metadata of the whole collection is frozen
+  pub fn can_edit_collection(
+    storage: &dyn Storage
+  ) -> Result<(), ContractError> {
+    if let Some(freeze) = METADATA_FREEZE.may_load(storage)? {
+      if freeze.all.is_some() {
+        return Err(ContractError::MetadataFrozen { token_id: "*".to_string() });
+      }
+    }
+
+    Ok(())
+  }
+
+  // fails once the metadata of the token is frozen, either by a collection wide freeze or by a
+  // frozen range containing the token id. a range covers {prefix}{start} to {prefix}{end}, so
+  // series (name-1), edition (template-1) and evolved (evolved-1) ids can be frozen by range too
+  pub fn can_edit_metadata(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<(), ContractError> {
+    if let Some(freeze) = METADATA_FREEZE.may_load(storage)? {
+      if freeze.all.is_some() {
+        return Err(ContractError::MetadataFrozen { token_id: token_id.to_string() });
+      }
+
+      let frozen = freeze.ranges.iter().any(|range| {
+        range_number(range, token_id).map_or(false, |id| id >= range.start && id <= range.end)
+      });
+      if frozen {
+        return Err(ContractError::MetadataFrozen { token_id: token_id.to_string() });
+      }
+    }
+
+    Ok(())
+  }
+
+  // every upgraded trait must be declared mutable in the schema of the token store_conf (see token_store_conf)
+  // and pass its checks. without a store_conf, or with a store_conf without schema, nothing can be upgraded
+  pub fn can_upgrade_traits(
+    conf: &Option<StoreConf>,
+    traits: &[Trait]
+  ) -> Result<(), ContractError> {
+    if traits.is_empty() {
+      return Err(ContractError::RequestTooSmall { size: 0 });
+    }
+
+    let columns = conf.as_ref().map(store_columns).unwrap_or_default();
+
+    for attr in traits {
+      let schema = columns
+        .iter()
+        .find(|c| c.trait_type == attr.trait_type && c.mutable)
+        .ok_or(ContractError::ImmutableTrait { trait_type: attr.trait_type.clone() })?;
+
+      check_trait_value(schema, &attr.value)
+        .map_err(|reason| ContractError::InvalidTrait { trait_type: attr.trait_type.clone(), reason })?;
+    }
+
+    Ok(())
+  }
+
+  // the next entry of the trait history of a token, also the nonce the owner signs when consent is required
+  pub fn next_upgrade_seq(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<u64, ContractError> {
+    let last = TRAIT_HISTORY
+      .prefix(token_id)
+      .keys(storage, None, None, Order::Descending)
+      .next()
+      .transpose()?;
+
+    Ok(last.map_or(0, |seq| seq + 1))
+  }
+
+  // owners that registered a key must sign every upgrade of their tokens.
+  // the signed payload holds the history seq, so a consent can't be used twice
+  pub fn can_upgrade_with_consent(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    payload: &UpgradePayload,
+    consent: &Option<Binary>
+  ) -> Result<bool, ContractError> {
+    let key = match UPGRADE_CONSENT.may_load(deps.storage, owner)? {
+      Some(key) => key,
+      None => return Ok(false),
+    };
+
+    let signature = consent.as_ref().ok_or(ContractError::ConsentRequired { owner: owner.to_string() })?;
+
+    if !verify_signature(deps, env, &key, &to_binary(payload)?, signature) {
+      return Err(ContractError::InvalidSignature {});
+    }
+
+    Ok(true)
+  }
+
+  // sets the new values in place, traits the token didn't have are added at the end
+  pub fn apply_traits(
+    extension: &mut Extension,
+    traits: &[Trait]
+  ) -> Vec<Option<String>> {
+    let metadata = extension.get_or_insert_with(Default::default);
+    let attributes = metadata.attributes.get_or_insert_with(Vec::new);
+
+    traits
+      .iter()
+      .map(|attr| match attributes.iter_mut().find(|a| a.trait_type == attr.trait_type) {
+        Some(current) => Some(std::mem::replace(&mut current.value, attr.value.clone())),
+        None => {
+          attributes.push(attr.clone());
+          None
+        },
+      })
+      .collect()
+  }
+
+  // the traits of an evolved token. every rule combines the numeric values of its trait in the inputs,
+  // the traits without a rule are copied from the first input. the result still has to pass the
+  // store_conf schema of the first input, so a max on a tier column is also the last tier an evolution can reach
+  pub fn evolve_traits(
+    conf: &Option<StoreConf>,
+    rules: &EvolveRules,
+    inputs: &[Extension]
+  ) -> Result<Vec<Trait>, ContractError> {
+    let mut traits = inputs.first().map(token_traits).unwrap_or_default();
+    let columns = conf.as_ref().map(store_columns).unwrap_or_default();
+
+    for rule in &rules.traits {
+      let invalid = |reason: String| ContractError::InvalidEvolve { reason };
+
+      let mut values: Vec<i64> = vec![];
+      for input in inputs {
+        let value = token_traits(input)
+          .into_iter()
+          .find(|a| a.trait_type == rule.trait_type)
+          .ok_or_else(|| invalid(format!("every input needs a {}", rule.trait_type)))?;
+
+        values.push(value.value
+          .parse()
+          .map_err(|_| invalid(format!("{} must be a number", rule.trait_type)))?);
+      }
+
+      let highest = values.into_iter().max().unwrap_or_default();
+      let value = match rule.op {
+        EvolveOp::Highest => highest,
+        EvolveOp::HighestPlusOne => highest + 1,
+      }.to_string();
+
+      if let Some(schema) = columns.iter().find(|c| c.trait_type == rule.trait_type) {
+        check_trait_value(schema, &value).map_err(invalid)?;
+      }
+
+      match traits.iter_mut().find(|a| a.trait_type == rule.trait_type) {
+        Some(current) => current.value = value,
+        None => traits.push(Trait {
+          display_type: None,
+          trait_type: rule.trait_type.clone(),
+          value,
+        }),
+      }
+    }
+
+    Ok(traits)
+  }
+
+  // edition ids are {template_id}-{number}, returns the template a token id belongs to if any
+  pub fn edition_template_of(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<Option<String>, ContractError> {
+    if let Some((template_id, number)) = token_id.rsplit_once('-') {
+      if number.parse::<u64>().is_ok() && EDITIONS.has(storage, template_id) {
+        return Ok(Some(template_id.to_string()));
+      }
+    }
+
+    Ok(None)
+  }
+
+  // the ids of an edition are its own from the moment it is stored, no other token can be stored under them
+  pub fn not_edition_id(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<(), ContractError> {
+    if edition_template_of(storage, token_id)?.is_some() {
+      return Err(ContractError::ReservedTokenId { token_id: token_id.to_string() });
+    }
+
+    Ok(())
+  }
+
+  // a new edition takes every id {template_id}-{number}, so none of them can be stored, minted or burnt
+  // already. the evolved- ids belong to evolve
+  pub fn can_reserve_edition(
+    storage: &dyn Storage,
+    contract: &CW721Contract,
+    template_id: &str
+  ) -> Result<(), ContractError> {
+    let prefix = format!("{}-", template_id);
+    if prefix == EVOLVED_TOKEN_PREFIX {
+      return Err(ContractError::ReservedTokenId { token_id: prefix });
+    }
+
+    // '.' comes right after '-', the range holds every id starting with the prefix
+    let end = format!("{}.", template_id);
+    let numbered = |token_id: &String| token_id[prefix.len()..].parse::<u64>().is_ok();
+
+    let tokens = contract.tokens.keys(
+      storage,
+      Some(Bound::InclusiveRaw(prefix.clone().into_bytes())),
+      Some(Bound::ExclusiveRaw(end.clone().into_bytes())),
+      Order::Ascending
+    );
+    let burnt = BURNED.keys(
+      storage,
+      Some(Bound::InclusiveRaw(prefix.clone().into_bytes())),
+      Some(Bound::ExclusiveRaw(end.into_bytes())),
+      Order::Ascending
+    );
+
+    for token_id in tokens.chain(burnt) {
+      let token_id = token_id?;
+      if numbered(&token_id) {
+        return Err(ContractError::ReservedTokenId { token_id });
+      }
+    }
+
+    Ok(())
+  }
+
+  // the template of an edition is written by hand, it gets the uri checks of a hand stored token. the
+  // edition trait is added to every copy, the template can't bring its own
+  pub fn validate_edition(
+    metadata: &Metadata,
+    token_uri: &Option<String>
+  ) -> Result<(), ContractError> {
+    validate_metadata(&None, metadata)?;
+
+    if let Some(uri) = token_uri {
+      if !URI_PREFIXES.iter().any(|prefix| uri.starts_with(prefix)) {
+        return Err(ContractError::InvalidMetadata { reason: "token_uri must start with ipfs://, ar:// or https://".to_string() });
+      }
+    }
+
+    let traits = metadata.attributes.as_deref().unwrap_or_default();
+    if traits.iter().any(|attr| attr.trait_type == EDITION_TRAIT) {
+      return Err(ContractError::InvalidEdition { reason: format!("the {} trait is added to every copy", EDITION_TRAIT) });
+    }
+
+    Ok(())
+  }
+
+  // the token of edition 'number', a copy of the template metadata named and marked with its edition number
+  pub fn edition_token(
+    template_id: &str,
+    edition: &Edition,
+    number: u64
+  ) -> (String, Extension) {
+    let mut metadata = edition.metadata.clone();
+
+    metadata.name = Some(format!("{} #{}", metadata.name.unwrap_or_else(|| template_id.to_string()), number));
+    metadata.attributes
+      .get_or_insert_with(Vec::new)
+      .push(Trait {
+        display_type: Some("number".to_string()),
+        trait_type: EDITION_TRAIT.to_string(),
+        value: number.to_string(),
+      });
+
+    (format!("{}-{}", template_id, number), Some(metadata))
+  }
+
+  // PROVENANCE KEEPS THE SHA256 OF (token_uri, metadata) OF EVERY STORED TOKEN, SO HOLDERS CAN CHECK THAT
+  // WHAT THEY MINTED IS WHAT WAS STORED. it is written on store, rewritten by UpdateStored and dropped
+  // with the token when a stored token is retired
+  pub fn record_provenance(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    token_uri: &Option<String>,
+    extension: &Extension
+  ) -> Result<(), ContractError> {
+    let hash = Sha256::digest(to_binary(&(token_uri, extension))?.as_slice());
+    PROVENANCE.save(storage, token_id, &Binary::from(hash.as_slice()))?;
+    Ok(())
+  }
+
+  // every store_conf a StoreConf call stored rows with is kept under a version, and every row under the version
+  // it was built from. the collection and series confs can change later, the rows keep the rules they passed
+  pub fn save_store_conf(
+    storage: &mut dyn Storage,
+    conf: &StoreConf
+  ) -> Result<u64, ContractError> {
+    let version = CONF_VERSION.may_load(storage)?.unwrap_or_default() + 1;
+    STORE_CONFS.save(storage, version, conf)?;
+    CONF_VERSION.save(storage, &version)?;
+    Ok(version)
+  }
+
+  // the store_conf a token was stored with. tokens stored by hand (Store, StoreBatch, LazyMint) have none
+  pub fn token_store_conf(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<Option<StoreConf>, ContractError> {
+    match TOKEN_CONF.may_load(storage, token_id)? {
+      Some(version) => Ok(Some(STORE_CONFS.load(storage, version)?)),
+      None => Ok(None),
+    }
+  }
+
+  // the schema trait upgrades and evolutions are checked against, the one the token was stored with.
+  // tokens stored by hand fall back to the collection store_conf
+  pub fn token_schema_conf(
+    storage: &dyn Storage,
+    token_id: &str
+  ) -> Result<Option<StoreConf>, ContractError> {
+    match token_store_conf(storage, token_id)? {
+      Some(conf) => Ok(Some(conf)),
+      None => Ok(CONFIG.load(storage)?.store_conf),
+    }
+  }
+
+  // the checks store_conf runs on a row and on its uris, for metadata written by hand (UpdateStored).
+  // without a store_conf only the uri prefixes are checked
+  pub fn validate_metadata(
+    conf: &Option<StoreConf>,
+    metadata: &Metadata
+  ) -> Result<(), ContractError> {
+    let uris = [
+      ("image", &metadata.image),
+      ("animation_url", &metadata.animation_url),
+      ("external_url", &metadata.external_url),
+    ];
+
+    for (field, uri) in uris {
+      if let Some(uri) = uri {
+        if !URI_PREFIXES.iter().any(|prefix| uri.starts_with(prefix)) {
+          return Err(ContractError::InvalidMetadata { reason: format!("{} must start with ipfs://, ar:// or https://", field) });
+        }
+      }
+    }
+
+    let conf = match conf {
+      Some(conf) => conf,
+      None => return Ok(()),
+    };
+
+    let columns = store_columns(conf);
+    let traits = metadata.attributes.clone().unwrap_or_default();
+
+    for attr in &traits {
+      let schema = columns
+        .iter()
+        .find(|c| c.trait_type == attr.trait_type)
+        .ok_or(ContractError::InvalidTrait { trait_type: attr.trait_type.clone(), reason: "not in the schema".to_string() })?;
+
+      check_trait_value(schema, &attr.value)
+        .map_err(|reason| ContractError::InvalidTrait { trait_type: attr.trait_type.clone(), reason })?;
+    }
+
+    for column in columns.iter().filter(|c| c.required) {
+      if !traits.iter().any(|attr| attr.trait_type == column.trait_type) {
+        return Err(ContractError::InvalidTrait { trait_type: column.trait_type.clone(), reason: "is required".to_string() });
+      }
+    }
+
+    Ok(())
+  }